@@ -1,6 +1,7 @@
 mod middleware;
 
 use crate::auth::{openid, pat, AuthError, UserInformation};
+use crate::core::config::Reloadable;
 use ::openid::{Claims, CustomClaims};
 use chrono::{DateTime, LocalResult, TimeZone, Utc};
 pub use middleware::AuthenticatedUntil;
@@ -49,6 +50,13 @@ pub enum AuthN {
         openid: Option<openid::Authenticator>,
         token: Option<pat::Authenticator>,
     },
+    /// Authentication is enabled, following changes of the openid authenticator.
+    ///
+    /// While the current openid authenticator is [`None`], authentication is disabled.
+    Reloading {
+        openid: Reloadable<Option<openid::Authenticator>>,
+        token: Option<pat::Authenticator>,
+    },
 }
 
 /// Map a combination of openid and PAT authenticator
@@ -63,6 +71,24 @@ impl From<(Option<openid::Authenticator>, Option<pat::Authenticator>)> for AuthN
     }
 }
 
+/// Map a combination of a reloadable openid and a PAT authenticator
+impl
+    From<(
+        Reloadable<Option<openid::Authenticator>>,
+        Option<pat::Authenticator>,
+    )> for AuthN
+{
+    fn from(
+        auth: (
+            Reloadable<Option<openid::Authenticator>>,
+            Option<pat::Authenticator>,
+        ),
+    ) -> Self {
+        let (openid, token) = auth;
+        AuthN::Reloading { openid, token }
+    }
+}
+
 impl AuthN {
     #[instrument(skip_all, err)]
     async fn authenticate(
@@ -74,59 +100,76 @@ impl AuthN {
                 // authentication disabled
                 Ok((UserInformation::Anonymous, None))
             }
-            Self::Enabled { openid, token } => match credentials {
-                Credentials::AccessToken(creds) => {
-                    if let Some(token) = token {
-                        if creds.access_token.is_none() {
-                            log::debug!("Cannot authenticate : empty access token.");
-                            return Err(AuthError::InvalidRequest(String::from(
-                                "No access token provided.",
-                            )));
+            Self::Enabled { openid, token } => {
+                Self::authenticate_with(openid.as_ref(), token.as_ref(), credentials).await
+            }
+            Self::Reloading { openid, token } => match openid.current().as_ref() {
+                // authentication currently disabled
+                None => Ok((UserInformation::Anonymous, None)),
+                Some(openid) => {
+                    Self::authenticate_with(Some(openid), token.as_ref(), credentials).await
+                }
+            },
+        }
+    }
+
+    async fn authenticate_with(
+        openid: Option<&openid::Authenticator>,
+        token: Option<&pat::Authenticator>,
+        credentials: Credentials,
+    ) -> Result<(UserInformation, Option<DateTime<Utc>>), AuthError> {
+        match credentials {
+            Credentials::AccessToken(creds) => {
+                if let Some(token) = token {
+                    if creds.access_token.is_none() {
+                        log::debug!("Cannot authenticate : empty access token.");
+                        return Err(AuthError::InvalidRequest(String::from(
+                            "No access token provided.",
+                        )));
+                    }
+                    let auth_response = token
+                        .authenticate(pat::Request {
+                            user_id: creds.username.clone(),
+                            access_token: creds.access_token.clone().unwrap_or_default(),
+                        })
+                        .await
+                        .map_err(|e| AuthError::Internal(e.to_string()))?;
+                    match auth_response.outcome {
+                        pat::Outcome::Known(details) => {
+                            Ok((UserInformation::Authenticated(details), None))
                         }
-                        let auth_response = token
-                            .authenticate(pat::Request {
-                                user_id: creds.username.clone(),
-                                access_token: creds.access_token.clone().unwrap_or_default(),
-                            })
-                            .await
-                            .map_err(|e| AuthError::Internal(e.to_string()))?;
-                        match auth_response.outcome {
-                            pat::Outcome::Known(details) => {
-                                Ok((UserInformation::Authenticated(details), None))
-                            }
-                            pat::Outcome::Unknown => {
-                                log::debug!("Unknown access token");
-                                Err(AuthError::Forbidden)
-                            }
+                        pat::Outcome::Unknown => {
+                            log::debug!("Unknown access token");
+                            Err(AuthError::Forbidden)
                         }
-                    } else {
-                        log::debug!("Access token authentication disabled");
-                        Err(AuthError::InvalidRequest(
-                            "Access token authentication disabled".to_string(),
-                        ))
                     }
+                } else {
+                    log::debug!("Access token authentication disabled");
+                    Err(AuthError::InvalidRequest(
+                        "Access token authentication disabled".to_string(),
+                    ))
                 }
-                Credentials::OpenIDToken(token) => {
-                    if let Some(openid) = openid {
-                        match openid.validate_token(&token).await {
-                            Ok(token) => Ok((
-                                UserInformation::Authenticated(token.clone().into()),
-                                Some(to_expiration(token.standard_claims().exp())?),
-                            )),
-                            Err(err) => {
-                                log::debug!("Authentication error: {err}");
-                                Err(AuthError::Forbidden)
-                            }
+            }
+            Credentials::OpenIDToken(token) => {
+                if let Some(openid) = openid {
+                    match openid.validate_token(&token).await {
+                        Ok(token) => Ok((
+                            UserInformation::Authenticated(token.clone().into()),
+                            Some(to_expiration(token.standard_claims().exp())?),
+                        )),
+                        Err(err) => {
+                            log::debug!("Authentication error: {err}");
+                            Err(AuthError::Forbidden)
                         }
-                    } else {
-                        log::debug!("Open ID authentication disabled");
-                        Err(AuthError::InvalidRequest(
-                            "Open ID authentication disabled".to_string(),
-                        ))
                     }
+                } else {
+                    log::debug!("Open ID authentication disabled");
+                    Err(AuthError::InvalidRequest(
+                        "Open ID authentication disabled".to_string(),
+                    ))
                 }
-                Credentials::Anonymous => Ok((UserInformation::Anonymous, None)),
-            },
+            }
+            Credentials::Anonymous => Ok((UserInformation::Anonymous, None)),
         }
     }
 }
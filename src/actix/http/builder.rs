@@ -1,9 +1,14 @@
 use super::{bind::bind_http, config::HttpConfig};
-use crate::actix::http::{BuildCors, CorsConfig};
-use crate::app::{Startup, StartupExt};
+use crate::actix::http::{BuildCors, CorsConfig, ReloadableCors};
+use crate::app::Startup;
 use crate::{
     app::RuntimeConfig,
-    core::tls::{TlsAuthConfig, WithTlsAuthConfig},
+    core::{
+        config::Reloadable,
+        task::{ShutdownToken, Task},
+        tls::{TlsAuthConfig, WithTlsAuthConfig},
+        Spawner,
+    },
 };
 use actix_cors::Cors;
 use actix_http::Extensions;
@@ -25,11 +30,13 @@ where
     F: Fn(&mut ServiceConfig) + Send + Clone + 'static,
 {
     config: HttpConfig,
+    reloadable: Option<Reloadable<HttpConfig>>,
     default_cors: Option<CorsConfig>,
     app_builder: Box<F>,
     on_connect: Option<Box<OnConnectFn>>,
     tls_auth_config: TlsAuthConfig,
     tracing: bool,
+    shutdown: Option<ShutdownToken>,
 }
 
 impl<F> HttpBuilder<F>
@@ -40,11 +47,13 @@ where
     pub fn new(config: HttpConfig, runtime: Option<&RuntimeConfig>, app_builder: F) -> Self {
         Self {
             config,
+            reloadable: None,
             default_cors: None,
             app_builder: Box::new(app_builder),
             on_connect: None,
             tls_auth_config: TlsAuthConfig::default(),
             tracing: runtime.map(|r| r.tracing.is_enabled()).unwrap_or_default(),
+            shutdown: None,
         }
     }
 
@@ -54,6 +63,15 @@ where
         self
     }
 
+    /// Use a reloadable configuration, for settings which may change at runtime.
+    ///
+    /// Currently, only changes of the CORS configuration are applied to the running server. All
+    /// other settings are taken from the [`HttpConfig`] provided when creating the builder.
+    pub fn reloadable(mut self, config: Reloadable<HttpConfig>) -> Self {
+        self.reloadable = Some(config);
+        self
+    }
+
     /// Set an "on connect" handler.
     pub fn on_connect<O>(mut self, on_connect: O) -> Self
     where
@@ -69,22 +87,22 @@ where
         self
     }
 
-    /// Start the server on the provided startup context.
-    pub fn start(self, startup: &mut dyn Startup) -> anyhow::Result<()> {
-        startup.spawn(self.run()?);
-        Ok(())
+    /// Set a shutdown token, gracefully stopping the server once triggered.
+    ///
+    /// This also disables the signal handling of the server itself.
+    pub fn shutdown_token(mut self, shutdown: ShutdownToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
     }
 
-    /// Get the effective CORS config.
+    /// Start the server on the provided startup context.
     ///
-    /// This will either the configuration provided through the [`HttpConfig`], or the one
-    /// registered by the application using [`Self::default_cors()`]
-    fn cors_config(&self) -> Option<CorsConfig> {
-        self.config
-            .cors
-            .as_ref()
-            .or(self.default_cors.as_ref())
-            .cloned()
+    /// The server will be gracefully stopped when the application shuts down.
+    pub fn start(self, startup: &mut dyn Startup) -> anyhow::Result<()> {
+        let shutdown = startup.shutdown_token();
+        startup
+            .spawn_task(Task::new("http-server", self.shutdown_token(shutdown).run()?).graceful());
+        Ok(())
     }
 
     /// Run the server.
@@ -93,9 +111,7 @@ where
     /// using [`crate::app::Startup`].
     ///
     /// In most cases you want to use [`Self::start`] instead.
-    pub fn run(
-        #[allow(unused_mut)] mut self,
-    ) -> Result<BoxFuture<'static, Result<(), anyhow::Error>>, anyhow::Error> {
+    pub fn run(mut self) -> Result<BoxFuture<'static, Result<(), anyhow::Error>>, anyhow::Error> {
         let max_payload_size = self.config.max_payload_size;
         let max_json_payload_size = self.config.max_json_payload_size;
 
@@ -107,21 +123,25 @@ where
         // FIXME: replace with direct conversion once nlopes/actix-web-prom#67 is merged
         .map_err(|err| anyhow::anyhow!("Failed to build prometheus middleware: {err}"))?;
 
-        let cors = self.cors_config();
-        log::debug!("Effective CORS config {cors:?}");
+        // the effective CORS config is either the one provided through the HTTP config, or the
+        // one registered by the application using `default_cors()`
+        let cors = ReloadableCors::new(
+            self.reloadable
+                .take()
+                .unwrap_or_else(|| Reloadable::fixed(self.config.clone())),
+            self.default_cors.take(),
+        );
 
-        // we just try to parse it once, so we can be sure it doesn't panic later
-        let _: Option<Cors> = cors.build_cors()?;
+        // we just try to parse it once, so we can be sure the initial configuration is valid
+        let _: Option<Cors> = cors.cors_config().build_cors()?;
 
         let mut main = HttpServer::new(move || {
             let app = App::new();
 
             // add wrapper (the last added is executed first)
 
-            // enable CORS support
-            // this should not panic, as we did parse the configuration once, before the http builder
-            let cors: Option<Cors> = cors.build_cors().expect("Configuration must be valid");
-            let app = app.wrap(Condition::from_option(cors));
+            // enable CORS support, following changes of the configuration
+            let app = app.wrap(cors.clone());
 
             // record request metrics
             let app = app.wrap(prometheus.clone());
@@ -174,6 +194,16 @@ where
             main = main.workers(workers)
         }
 
-        Ok(main.run().err_into().boxed())
+        Ok(match self.shutdown {
+            Some(shutdown) => {
+                let server = main.disable_signals().run();
+                let handle = server.handle();
+                shutdown
+                    .graceful(server, async move { handle.stop(true).await })
+                    .err_into()
+                    .boxed()
+            }
+            None => main.run().err_into().boxed(),
+        })
     }
 }
@@ -1,5 +1,8 @@
 use super::defaults;
-use crate::actix::http::CorsConfig;
+use crate::{
+    actix::http::CorsConfig,
+    core::config::{ConfigReference, Reference},
+};
 use serde::Deserialize;
 
 /// HTTP server configuration.
@@ -46,3 +49,62 @@ impl Default for HttpConfig {
         }
     }
 }
+
+impl ConfigReference for HttpConfig {
+    fn describe(r: &mut Reference) {
+        r.with_default(
+            "bind_addr",
+            "string",
+            defaults::bind_addr(),
+            "The address to bind the HTTP server to",
+        );
+        r.with_default(
+            "max_json_payload_size",
+            "integer",
+            defaults::max_json_payload_size(),
+            "The maximum size of a JSON payload, in bytes",
+        );
+        r.with_default(
+            "max_payload_size",
+            "integer",
+            defaults::max_payload_size(),
+            "The maximum size of a payload, in bytes",
+        );
+        r.with_default("disable_tls", "boolean", false, "Disable TLS");
+        r.with_default("disable_tls_psk", "boolean", false, "Disable TLS-PSK");
+        r.optional(
+            "cert_bundle_file",
+            "path",
+            "The certificate bundle (PEM) used for TLS",
+        );
+        r.optional("key_file", "path", "The private key (PEM) used for TLS");
+        r.optional(
+            "workers",
+            "integer",
+            "The number of workers, defaults to the number of CPUs",
+        );
+        r.optional(
+            "metrics_namespace",
+            "string",
+            "The namespace of the HTTP metrics, defaults to `drogue`",
+        );
+        r.nested_optional::<CorsConfig>("cors");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::from_reference;
+
+    #[test]
+    fn test_reference() {
+        let config: HttpConfig = from_reference(&[("cors.mode", "permissive".into())]);
+
+        assert_eq!(config.cert_bundle_file.as_deref(), Some("sample"));
+        assert_eq!(config.key_file.as_deref(), Some("sample"));
+        assert_eq!(config.workers, Some(1));
+        assert_eq!(config.metrics_namespace.as_deref(), Some("sample"));
+        assert!(matches!(config.cors, Some(CorsConfig::Permissive(_))));
+    }
+}
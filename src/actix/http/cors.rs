@@ -1,22 +1,33 @@
-use crate::core::config::CommaSeparatedVec;
-use actix_cors::Cors;
+use crate::actix::http::HttpConfig;
+use crate::core::config::{ConfigReference, Delimited, Reference, Reloadable};
+use actix_cors::{Cors, CorsMiddleware};
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+};
+use futures_core::future::LocalBoxFuture;
+use futures_util::FutureExt;
 use http::header::{HeaderName, InvalidHeaderName};
 use http::method::InvalidMethod;
 use http::Method;
 use serde::Deserialize;
-use std::str::FromStr;
+use std::cell::RefCell;
+use std::future::{ready, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 #[derive(Clone, Debug, Default, Deserialize)]
 pub struct CorsSettings {
     #[serde(default)]
-    pub allowed_origin_urls: Option<CommaSeparatedVec>,
+    pub allowed_origin_urls: Option<Delimited<String>>,
 
     #[serde(default)]
-    pub allowed_methods: Option<CommaSeparatedVec>,
+    pub allowed_methods: Option<Delimited<Method>>,
 
     #[serde(default)]
-    pub allowed_headers: Option<CommaSeparatedVec>,
+    pub allowed_headers: Option<Delimited<HeaderName>>,
 
     #[serde(default)]
     pub allow_any_method: bool,
@@ -28,7 +39,7 @@ pub struct CorsSettings {
     pub allow_any_origin: bool,
 
     #[serde(default)]
-    pub expose_headers: Option<CommaSeparatedVec>,
+    pub expose_headers: Option<Delimited<HeaderName>>,
 
     #[serde(default)]
     #[serde(with = "humantime_serde")]
@@ -74,6 +85,75 @@ impl CorsConfig {
     }
 }
 
+impl ConfigReference for CorsConfig {
+    fn describe(r: &mut Reference) {
+        r.required(
+            "mode",
+            "`disabled` | `permissive` | `custom`",
+            "The CORS mode, settings are applied to `permissive` and `custom`",
+        );
+        r.flatten::<CorsSettings>();
+    }
+}
+
+impl ConfigReference for CorsSettings {
+    fn describe(r: &mut Reference) {
+        r.optional(
+            "allowed_origin_urls",
+            "comma separated list",
+            "The allowed origins",
+        );
+        r.optional(
+            "allowed_methods",
+            "comma separated list",
+            "The allowed HTTP methods",
+        );
+        r.optional(
+            "allowed_headers",
+            "comma separated list",
+            "The allowed HTTP headers",
+        );
+        r.with_default("allow_any_method", "boolean", false, "Allow any method");
+        r.with_default("allow_any_header", "boolean", false, "Allow any header");
+        r.with_default("allow_any_origin", "boolean", false, "Allow any origin");
+        r.optional(
+            "expose_headers",
+            "comma separated list",
+            "The HTTP headers to expose",
+        );
+        r.optional(
+            "max_age",
+            "duration",
+            "The maximum age of a preflight request",
+        );
+        r.with_default(
+            "disable_preflight",
+            "boolean",
+            false,
+            "Disable preflight requests",
+        );
+        r.with_default(
+            "send_wildcard",
+            "boolean",
+            false,
+            "Send a wildcard instead of the origin",
+        );
+        r.with_default(
+            "disable_vary_header",
+            "boolean",
+            false,
+            "Disable the `Vary` header",
+        );
+        r.with_default("expose_any_header", "boolean", false, "Expose any header");
+        r.with_default(
+            "supports_credentials",
+            "boolean",
+            false,
+            "Allow sending credentials",
+        );
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum CorsConfigError {
     #[error("Invalid HTTP header name: {0}")]
@@ -88,18 +168,18 @@ impl CorsSettings {
             cors = cors.max_age(max_age);
         }
 
-        if let Some(headers) = self.allowed_headers()? {
-            cors = cors.allowed_headers(headers);
+        if let Some(headers) = &self.allowed_headers {
+            cors = cors.allowed_headers(headers.to_vec());
         }
 
         if let Some(origin) = &self.allowed_origin_urls {
-            for url in &origin.0 {
+            for url in origin {
                 cors = cors.allowed_origin(url.as_str());
             }
         }
 
-        if let Some(methods) = self.allowed_methods()? {
-            cors = cors.allowed_methods(methods);
+        if let Some(methods) = &self.allowed_methods {
+            cors = cors.allowed_methods(methods.to_vec());
         }
 
         if self.send_wildcard {
@@ -130,8 +210,8 @@ impl CorsSettings {
             cors = cors.supports_credentials();
         }
 
-        if let Some(headers) = self.expose_headers()? {
-            cors = cors.expose_headers(headers);
+        if let Some(headers) = &self.expose_headers {
+            cors = cors.expose_headers(headers.to_vec());
         }
 
         if self.expose_any_header {
@@ -140,47 +220,6 @@ impl CorsSettings {
 
         Ok(cors)
     }
-
-    /// Evaluate the allowed headers.
-    fn allowed_headers(&self) -> Result<Option<Vec<HeaderName>>, InvalidHeaderName> {
-        Self::convert_headers(&self.allowed_headers)
-    }
-
-    /// Evaluate the expose headers.
-    fn expose_headers(&self) -> Result<Option<Vec<HeaderName>>, InvalidHeaderName> {
-        Self::convert_headers(&self.expose_headers)
-    }
-
-    /// Convert headers from string to [`HeaderName].
-    ///
-    /// Failing the operation if one of the conversions fails.
-    fn convert_headers(
-        headers: &Option<CommaSeparatedVec>,
-    ) -> Result<Option<Vec<HeaderName>>, InvalidHeaderName> {
-        headers
-            .as_ref()
-            .map(|csv| &csv.0)
-            .map(|headers| {
-                headers
-                    .iter()
-                    .map(|h| HeaderName::from_str(h))
-                    .collect::<Result<_, _>>()
-            })
-            .transpose()
-    }
-
-    fn allowed_methods(&self) -> Result<Option<Vec<Method>>, InvalidMethod> {
-        self.allowed_methods
-            .as_ref()
-            .map(|csv| &csv.0)
-            .map(|methods| {
-                methods
-                    .iter()
-                    .map(|m| Method::from_str(m))
-                    .collect::<Result<_, _>>()
-            })
-            .transpose()
-    }
 }
 
 pub trait BuildCors {
@@ -206,6 +245,141 @@ impl BuildCors for Option<CorsConfig> {
     }
 }
 
+/// A CORS middleware, following the changes of a [`Reloadable`] HTTP configuration.
+///
+/// The CORS configuration of the [`HttpConfig`] takes precedence over the default one. Once the
+/// configuration changed, the [`Cors`] middleware gets re-created with the next request. If the
+/// new configuration is invalid, the previous one is kept.
+#[derive(Clone)]
+pub struct ReloadableCors {
+    config: Reloadable<HttpConfig>,
+    default: Option<CorsConfig>,
+}
+
+impl ReloadableCors {
+    pub fn new(config: Reloadable<HttpConfig>, default: Option<CorsConfig>) -> Self {
+        Self { config, default }
+    }
+
+    /// Get the currently effective CORS config.
+    pub fn cors_config(&self) -> Option<CorsConfig> {
+        self.effective(&self.config.current())
+    }
+
+    fn effective(&self, config: &HttpConfig) -> Option<CorsConfig> {
+        config.cors.as_ref().or(self.default.as_ref()).cloned()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ReloadableCors
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = ReloadableCorsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ReloadableCorsMiddleware {
+            service: Rc::new(service),
+            cors: self.clone(),
+            current: RefCell::new(None),
+        }))
+    }
+}
+
+/// The service created by [`ReloadableCors`].
+pub struct ReloadableCorsMiddleware<S> {
+    service: Rc<S>,
+    cors: ReloadableCors,
+    current: RefCell<Option<Current<S>>>,
+}
+
+/// The CORS middleware, built for a specific configuration.
+struct Current<S> {
+    config: Arc<HttpConfig>,
+    cors: Option<CorsMiddleware<Shared<S>>>,
+}
+
+impl<S, B> ReloadableCorsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    fn build(&self, config: Arc<HttpConfig>, previous: Option<Current<S>>) -> Current<S> {
+        let cors = match self.cors.effective(&config).build_cors() {
+            Ok(cors) => cors.and_then(|cors| {
+                cors.new_transform(Shared(self.service.clone()))
+                    .now_or_never()
+                    .and_then(Result::ok)
+            }),
+            Err(err) => {
+                log::warn!("Keeping previous CORS configuration: {err}");
+                previous.and_then(|previous| previous.cors)
+            }
+        };
+
+        Current { config, cors }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for ReloadableCorsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let config = self.cors.config.current();
+
+        let mut current = self.current.borrow_mut();
+        if !matches!(&*current, Some(current) if Arc::ptr_eq(&current.config, &config)) {
+            log::debug!("Effective CORS config {:?}", self.cors.effective(&config));
+            let previous = current.take();
+            *current = Some(self.build(config, previous));
+        }
+
+        match current.as_ref().and_then(|current| current.cors.as_ref()) {
+            Some(cors) => Box::pin(cors.call(req)),
+            None => {
+                let fut = self.service.call(req);
+                Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+            }
+        }
+    }
+}
+
+/// Shares the inner service between the re-created CORS middlewares.
+struct Shared<S>(Rc<S>);
+
+impl<S, Req> Service<Req> for Shared<S>
+where
+    S: Service<Req>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&self, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(ctx)
+    }
+
+    fn call(&self, req: Req) -> Self::Future {
+        self.0.call(req)
+    }
+}
+
 /// Testing stuff.
 ///
 /// Unfortunately `Cors` doesn't allow to be inspected. This means, that we have a hard time
@@ -215,7 +389,14 @@ impl BuildCors for Option<CorsConfig> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::core::config::ConfigFromEnv;
+    use crate::core::config::{
+        testing::{from_reference, TempDir},
+        ConfigFromEnv, ConfigReloader, ConfigSources,
+    };
+    use actix_web::{
+        http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN},
+        test, web, App, HttpResponse,
+    };
     use config::Environment;
     use std::collections::HashMap;
 
@@ -302,4 +483,69 @@ mod test {
         assert!(debug.contains("https://foo.bar"));
         assert!(debug.contains("https://bar.baz/*"));
     }
+
+    #[actix_web::test]
+    async fn test_reload() {
+        let dir = TempDir::new("cors");
+        let path = dir.join("config.yaml");
+        std::fs::write(&path, "http:\n  cors:\n    mode: disabled\n").unwrap();
+
+        let sources = ConfigSources::new()
+            .file(&path)
+            .environment(Environment::default().source(Some(HashMap::new())));
+        let reloader = ConfigReloader::<HttpConfig>::new(sources, Some("http")).unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(ReloadableCors::new(reloader.subscribe(), None))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let request = || {
+            test::TestRequest::get()
+                .uri("/")
+                .insert_header((ORIGIN, "https://example.com"))
+                .to_request()
+        };
+
+        let response = test::call_service(&app, request()).await;
+        assert!(response.status().is_success());
+        assert!(response
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+
+        // enable CORS
+
+        std::fs::write(&path, "http:\n  cors:\n    mode: permissive\n").unwrap();
+        reloader.reload().unwrap();
+
+        let response = test::call_service(&app, request()).await;
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_reference() {
+        let config: CorsConfig = from_reference(&[("mode", "custom".into())]);
+        match config {
+            CorsConfig::Custom(settings) => assert!(settings.max_age.is_some()),
+            other => panic!("Unexpected config: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reference_settings() {
+        let settings: CorsSettings = from_reference(&[("allowed_methods", "GET".into())]);
+
+        assert!(settings.allowed_origin_urls.is_some());
+        assert!(settings.allowed_methods.is_some());
+        assert!(settings.allowed_headers.is_some());
+        assert!(settings.expose_headers.is_some());
+        assert_eq!(settings.max_age, Some(Duration::from_secs(1)));
+    }
 }
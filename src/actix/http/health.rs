@@ -0,0 +1,174 @@
+use super::HttpConfig;
+use crate::health::{Criticality, HealthCheckError, HealthChecked};
+use async_trait::async_trait;
+use openssl::{asn1::Asn1Time, x509::X509};
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A health check, verifying that the certificates of a PEM file don't expire soon.
+///
+/// As the certificates are still valid, a failure only reports the application as degraded.
+pub struct CertificateExpiryCheck {
+    path: PathBuf,
+    threshold: Duration,
+}
+
+impl CertificateExpiryCheck {
+    /// Create a new check, failing when a certificate expires within the threshold.
+    pub fn new<P: Into<PathBuf>>(path: P, threshold: Duration) -> Self {
+        Self {
+            path: path.into(),
+            threshold,
+        }
+    }
+
+    /// Create a check for the certificate bundle of the HTTP server.
+    ///
+    /// Returns [`None`] if TLS is disabled, or no certificate bundle is configured.
+    pub fn from_config(config: &HttpConfig, threshold: Duration) -> Option<Self> {
+        match (config.disable_tls, &config.cert_bundle_file) {
+            (false, Some(file)) => Some(Self::new(file, threshold)),
+            _ => None,
+        }
+    }
+
+    async fn check(&self) -> Result<(), HealthCheckError> {
+        // reading the file is blocking, so don't do this on the executor
+        let path = self.path.clone();
+        let pem = tokio::task::spawn_blocking(move || std::fs::read(path))
+            .await
+            .map_err(HealthCheckError::from)?
+            .map_err(HealthCheckError::from)?;
+
+        self.verify(&pem)
+    }
+
+    fn verify(&self, pem: &[u8]) -> Result<(), HealthCheckError> {
+        let certs = X509::stack_from_pem(pem).map_err(HealthCheckError::from)?;
+
+        let deadline = (SystemTime::now() + self.threshold)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let deadline = Asn1Time::from_unix(deadline as _).map_err(HealthCheckError::from)?;
+
+        for cert in certs {
+            if cert.not_after() < deadline {
+                return HealthCheckError::nok(format!(
+                    "Certificate {:?} of '{}' expires at {}",
+                    cert.subject_name(),
+                    self.path.display(),
+                    cert.not_after()
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HealthChecked for CertificateExpiryCheck {
+    fn name(&self) -> String {
+        format!("certificate-expiry({})", self.path.display())
+    }
+
+    fn criticality(&self) -> Criticality {
+        Criticality::NonCritical
+    }
+
+    async fn is_ready(&self) -> Result<(), HealthCheckError> {
+        self.check().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::TempDir;
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::PKey,
+        x509::X509Name,
+    };
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    /// Create a self-signed certificate, valid until `now + expires_in` (in seconds).
+    fn certificate(expires_in: i64) -> Vec<u8> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+        let mut name = X509Name::builder().unwrap();
+        name.append_entry_by_text("CN", "localhost").unwrap();
+        let name = name.build();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&name).unwrap();
+        cert.set_issuer_name(&name).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::from_unix((now - 30 * DAY) as _).unwrap())
+            .unwrap();
+        cert.set_not_after(&Asn1Time::from_unix((now + expires_in) as _).unwrap())
+            .unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+        cert.build().to_pem().unwrap()
+    }
+
+    async fn check(pem: &[u8]) -> Result<(), HealthCheckError> {
+        let dir = TempDir::new("cert-expiry");
+        let path = dir.join("bundle.pem");
+        std::fs::write(&path, pem).unwrap();
+
+        CertificateExpiryCheck::new(path, Duration::from_secs(14 * DAY as u64))
+            .is_ready()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_valid() {
+        assert!(check(&certificate(90 * DAY)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expires_soon() {
+        let result = check(&certificate(7 * DAY)).await;
+        assert!(matches!(result, Err(HealthCheckError::NotOk(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expired() {
+        let result = check(&certificate(-DAY)).await;
+        assert!(matches!(result, Err(HealthCheckError::NotOk(_))));
+    }
+
+    #[tokio::test]
+    async fn test_bundle() {
+        // a single certificate expiring soon fails the whole bundle
+        let mut pem = certificate(90 * DAY);
+        pem.extend(certificate(7 * DAY));
+
+        let result = check(&pem).await;
+        assert!(matches!(result, Err(HealthCheckError::NotOk(_))));
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let dir = TempDir::new("cert-expiry");
+        let result = CertificateExpiryCheck::new(dir.join("missing.pem"), Duration::from_secs(0))
+            .is_ready()
+            .await;
+        assert!(matches!(result, Err(HealthCheckError::Failed(_))));
+    }
+}
@@ -5,8 +5,12 @@ mod builder;
 mod config;
 mod cors;
 mod defaults;
+#[cfg(feature = "openssl")]
+mod health;
 
 pub use self::config::*;
 pub use bind::*;
 pub use builder::*;
 pub use cors::*;
+#[cfg(feature = "openssl")]
+pub use health::*;
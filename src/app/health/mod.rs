@@ -1,6 +1,5 @@
 mod run;
 
-pub use run::{HealthChecker, HealthServerConfig};
-
-#[cfg(feature = "actix")]
-pub use run::HealthServer;
+pub use run::{
+    CheckHandle, CheckResult, HealthChecker, HealthReport, HealthServer, HealthServerConfig,
+};
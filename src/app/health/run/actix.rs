@@ -1,5 +1,13 @@
-use super::{HealthChecker, HealthServerConfig};
+use super::{HealthChecker, HealthServerConfig, InstanceInfo};
+use crate::{
+    app::RuntimeConfig,
+    core::{
+        info::ComponentInformation,
+        task::{ShutdownToken, TaskMonitor},
+    },
+};
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use futures_util::{future::err, TryFutureExt};
 use prometheus::Registry;
 use serde_json::json;
@@ -10,6 +18,8 @@ pub struct HealthServer {
     config: HealthServerConfig,
     checker: HealthChecker,
     registry: Option<Registry>,
+    info: InstanceInfo,
+    shutdown: Option<ShutdownToken>,
 }
 
 macro_rules! health_endpoint {
@@ -18,53 +28,147 @@ macro_rules! health_endpoint {
             $sys::HttpResponse::Ok().json(&json!({}))
         }
 
-        async fn readiness(checker: Data<HealthChecker>) -> $sys::HttpResponse {
-            let (code, body) = super::run_checks(checker.into_inner(), |checker| async move {
-                checker.is_ready().await
-            })
-            .await;
+        async fn info(
+            info: Data<InstanceInfo>,
+            checker: Data<HealthChecker>,
+        ) -> $sys::HttpResponse {
+            $sys::HttpResponse::Ok().json(&info.info(&checker).await)
+        }
+
+        async fn version(info: Data<InstanceInfo>) -> $sys::HttpResponse {
+            match info.version() {
+                Some(version) => $sys::HttpResponse::Ok().json(&version),
+                None => $sys::HttpResponse::NotFound().finish(),
+            }
+        }
+
+        async fn tasks(info: Data<InstanceInfo>) -> $sys::HttpResponse {
+            match info.tasks() {
+                Some(tasks) => $sys::HttpResponse::Ok().json(&tasks),
+                None => $sys::HttpResponse::NotFound().finish(),
+            }
+        }
+
+        async fn readiness(
+            req: $sys::HttpRequest,
+            checker: Data<HealthChecker>,
+        ) -> $sys::HttpResponse {
+            let verbose = super::is_verbose(req.query_string());
+            let (code, body) =
+                super::run_checks(checker.into_inner(), verbose, |checker| async move {
+                    checker.ready_report().await
+                })
+                .await;
             $sys::HttpResponse::build(code.into()).json(&body)
         }
 
-        async fn liveness(checker: Data<HealthChecker>) -> $sys::HttpResponse {
-            let (code, body) = super::run_checks(checker.into_inner(), |checker| async move {
-                checker.is_alive().await
-            })
-            .await;
+        async fn startup(
+            req: $sys::HttpRequest,
+            checker: Data<HealthChecker>,
+        ) -> $sys::HttpResponse {
+            let verbose = super::is_verbose(req.query_string());
+            let (code, body) =
+                super::run_checks(checker.into_inner(), verbose, |checker| async move {
+                    checker.startup_report()
+                })
+                .await;
+            $sys::HttpResponse::build(code.into()).json(&body)
+        }
+
+        async fn liveness(
+            req: $sys::HttpRequest,
+            checker: Data<HealthChecker>,
+        ) -> $sys::HttpResponse {
+            let verbose = super::is_verbose(req.query_string());
+            let (code, body) =
+                super::run_checks(checker.into_inner(), verbose, |checker| async move {
+                    checker.alive_report().await
+                })
+                .await;
             $sys::HttpResponse::build(code.into()).json(&body)
         }
     };
 }
 
 macro_rules! health_app {
-    ($checker:expr, $app_data:ident) => {
+    ($checker:expr, $info:expr, $app_data:ident) => {
         App::new()
             .$app_data($checker.clone())
+            .$app_data($info.clone())
             .route("/", web::get().to(index))
+            .route("/info", web::get().to(info))
+            .route("/version", web::get().to(version))
+            .route("/tasks", web::get().to(tasks))
+            .route("/startup", web::get().to(startup))
             .route("/readiness", web::get().to(readiness))
             .route("/liveness", web::get().to(liveness))
     };
 }
 
 impl HealthServer {
+    /// Create a new server.
+    ///
+    /// If a registry is provided, it is served by the `/metrics` endpoint, and the metrics of
+    /// the health checks get registered with it.
     pub fn new(
         config: HealthServerConfig,
         checker: HealthChecker,
         registry: Option<Registry>,
     ) -> Self {
+        if let Some(registry) = &registry {
+            if let Err(err) = checker.register_metrics(registry) {
+                log::warn!("Failed to register health check metrics: {err}");
+            }
+        }
+
         Self {
             config,
             checker,
             registry,
+            info: Default::default(),
+            shutdown: None,
         }
     }
 
+    /// Set the component information, provided by the `/info` and `/version` endpoints.
+    pub fn component(mut self, component: ComponentInformation) -> Self {
+        self.info.component = Some(component);
+        self
+    }
+
+    /// Set the runtime configuration, provided by the `/info` endpoint.
+    pub fn runtime_config(mut self, config: RuntimeConfig) -> Self {
+        self.info.runtime = Some(config);
+        self
+    }
+
+    /// Set the start time of the instance, defaults to the creation of the server.
+    pub fn started(mut self, started: DateTime<Utc>) -> Self {
+        self.info.started = started;
+        self
+    }
+
+    /// Set the task monitor, whose tasks are provided by the `/tasks` endpoint.
+    pub fn task_monitor(mut self, monitor: TaskMonitor) -> Self {
+        self.info.tasks = Some(monitor);
+        self
+    }
+
+    /// Set a shutdown token, gracefully stopping the server once triggered.
+    ///
+    /// This also disables the signal handling of the server itself.
+    pub fn shutdown_token(mut self, shutdown: ShutdownToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
     pub fn run(self) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>> {
         use actix_web::web;
         use actix_web::web::Data;
         health_endpoint!(actix_web);
 
         let checker = Data::new(self.checker);
+        let info = Data::new(self.info);
 
         let prometheus = match self.registry {
             Some(metrics) => actix_web_prom::PrometheusMetricsBuilder::new("health")
@@ -80,7 +184,7 @@ impl HealthServer {
         let http = actix_web::HttpServer::new(move || {
             use actix_web::App;
 
-            health_app!(checker, app_data).wrap(prometheus.clone())
+            health_app!(checker, info, app_data).wrap(prometheus.clone())
         });
 
         let http = match http.bind(self.config.bind_addr) {
@@ -88,11 +192,19 @@ impl HealthServer {
             Err(e) => return Box::pin(err(anyhow!(e))),
         };
 
-        let task = http
-            .workers(self.config.workers)
-            .run()
-            .map_err(|err| anyhow!(err));
+        let http = http.workers(self.config.workers);
 
-        Box::pin(task)
+        match self.shutdown {
+            Some(shutdown) => {
+                let server = http.disable_signals().run();
+                let handle = server.handle();
+                Box::pin(
+                    shutdown
+                        .graceful(server, async move { handle.stop(true).await })
+                        .map_err(|err| anyhow!(err)),
+                )
+            }
+            None => Box::pin(http.run().map_err(|err| anyhow!(err))),
+        }
     }
 }
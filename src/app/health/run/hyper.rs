@@ -0,0 +1,379 @@
+use super::{HealthChecker, HealthServerConfig, InstanceInfo};
+use crate::{
+    app::RuntimeConfig,
+    core::{
+        info::ComponentInformation,
+        task::{ShutdownToken, TaskMonitor},
+    },
+};
+use ::hyper::{
+    header::{HeaderValue, CONTENT_TYPE},
+    rt::Executor,
+    server::{
+        accept::Accept,
+        conn::{AddrIncoming, Http},
+    },
+    service::service_fn,
+    Body, Method, Request, Response, StatusCode,
+};
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use futures_core::future::LocalBoxFuture;
+use futures_util::{future::poll_fn, stream::FuturesUnordered, FutureExt, StreamExt};
+use prometheus::{Encoder, Registry, TextEncoder};
+use serde_json::{json, Value};
+use std::{convert::Infallible, future::Future, net::ToSocketAddrs, pin::Pin, sync::Arc};
+
+/// A server, running health check endpoints.
+///
+/// This is a lightweight implementation based on `hyper`, used when the `actix` feature is not
+/// enabled. All connections are handled by a single task, so the number of workers is ignored.
+pub struct HealthServer {
+    config: HealthServerConfig,
+    checker: HealthChecker,
+    registry: Option<Registry>,
+    info: InstanceInfo,
+    shutdown: Option<ShutdownToken>,
+}
+
+/// The state shared by all requests.
+struct State {
+    checker: Arc<HealthChecker>,
+    registry: Option<Registry>,
+    info: InstanceInfo,
+}
+
+impl HealthServer {
+    /// Create a new server.
+    ///
+    /// If a registry is provided, it is served by the `/metrics` endpoint, and the metrics of
+    /// the health checks get registered with it.
+    pub fn new(
+        config: HealthServerConfig,
+        checker: HealthChecker,
+        registry: Option<Registry>,
+    ) -> Self {
+        if let Some(registry) = &registry {
+            if let Err(err) = checker.register_metrics(registry) {
+                log::warn!("Failed to register health check metrics: {err}");
+            }
+        }
+
+        Self {
+            config,
+            checker,
+            registry,
+            info: Default::default(),
+            shutdown: None,
+        }
+    }
+
+    /// Set the component information, provided by the `/info` and `/version` endpoints.
+    pub fn component(mut self, component: ComponentInformation) -> Self {
+        self.info.component = Some(component);
+        self
+    }
+
+    /// Set the runtime configuration, provided by the `/info` endpoint.
+    pub fn runtime_config(mut self, config: RuntimeConfig) -> Self {
+        self.info.runtime = Some(config);
+        self
+    }
+
+    /// Set the start time of the instance, defaults to the creation of the server.
+    pub fn started(mut self, started: DateTime<Utc>) -> Self {
+        self.info.started = started;
+        self
+    }
+
+    /// Set the task monitor, whose tasks are provided by the `/tasks` endpoint.
+    pub fn task_monitor(mut self, monitor: TaskMonitor) -> Self {
+        self.info.tasks = Some(monitor);
+        self
+    }
+
+    /// Set a shutdown token, gracefully stopping the server once triggered.
+    pub fn shutdown_token(mut self, shutdown: ShutdownToken) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Run the server.
+    ///
+    /// The connections are handled by the returned future itself, so that the server runs as a
+    /// task of the application. The future completes once the server stopped. It doesn't spawn
+    /// any tasks, so it doesn't require a [`tokio::task::LocalSet`].
+    pub fn run(self) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+        async move {
+            let incoming = self.bind()?;
+            self.serve(incoming).await
+        }
+        .boxed_local()
+    }
+
+    fn bind(&self) -> anyhow::Result<AddrIncoming> {
+        let addr = self
+            .config
+            .bind_addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("Unable to resolve '{}'", self.config.bind_addr))?;
+
+        Ok(AddrIncoming::bind(&addr)?)
+    }
+
+    async fn serve(self, mut incoming: AddrIncoming) -> anyhow::Result<()> {
+        let state = Arc::new(State {
+            checker: Arc::new(self.checker),
+            registry: self.registry,
+            info: self.info,
+        });
+
+        let http = Http::new().with_executor(LocalExec);
+        let mut connections = FuturesUnordered::new();
+
+        let shutdown = self.shutdown.unwrap_or_else(ShutdownToken::never);
+        let shutdown = shutdown.triggered();
+        futures_util::pin_mut!(shutdown);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                accepted = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx)) => match accepted {
+                    Some(Ok(stream)) => {
+                        let state = state.clone();
+                        connections.push(http.serve_connection(
+                            stream,
+                            service_fn(move |request| handle(state.clone(), request)),
+                        ));
+                    }
+                    Some(Err(err)) => log::warn!("Failed to accept health connection: {err}"),
+                    None => break,
+                },
+                Some(result) = connections.next() => {
+                    if let Err(err) = result {
+                        log::debug!("Failed to serve health connection: {err}");
+                    }
+                }
+            }
+        }
+
+        // stop accepting connections, and let the open ones complete
+
+        drop(incoming);
+        for connection in Pin::new(&mut connections).iter_pin_mut() {
+            connection.graceful_shutdown();
+        }
+        while connections.next().await.is_some() {}
+
+        Ok(())
+    }
+}
+
+/// Refuses background tasks of connections, but is required by `hyper`.
+///
+/// HTTP/1 connections don't spawn any tasks. Still, the executor must accept the futures of the
+/// handlers, which are not [`Send`], as the results of the health checks are not. Spawning them
+/// locally would panic outside a [`tokio::task::LocalSet`], so they get dropped instead.
+#[derive(Clone, Copy, Debug)]
+struct LocalExec;
+
+impl<F> Executor<F> for LocalExec
+where
+    F: Future + 'static,
+{
+    fn execute(&self, _future: F) {
+        log::warn!("The health server doesn't support background tasks, dropping it");
+    }
+}
+
+async fn handle(state: Arc<State>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET {
+        return Ok(status(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    let verbose = super::is_verbose(request.uri().query().unwrap_or_default());
+    let checker = state.checker.clone();
+
+    let response = match request.uri().path() {
+        "/" => json(StatusCode::OK, &json!({})),
+        "/info" => json(StatusCode::OK, &state.info.info(&checker).await),
+        "/version" => optional(state.info.version()),
+        "/tasks" => optional(state.info.tasks()),
+        "/startup" => {
+            let (code, body) = super::run_checks(checker, verbose, |checker| async move {
+                checker.startup_report()
+            })
+            .await;
+            json(code, &body)
+        }
+        "/readiness" => {
+            let (code, body) = super::run_checks(checker, verbose, |checker| async move {
+                checker.ready_report().await
+            })
+            .await;
+            json(code, &body)
+        }
+        "/liveness" => {
+            let (code, body) = super::run_checks(checker, verbose, |checker| async move {
+                checker.alive_report().await
+            })
+            .await;
+            json(code, &body)
+        }
+        "/metrics" => match &state.registry {
+            Some(registry) => metrics(registry),
+            None => status(StatusCode::NOT_FOUND),
+        },
+        _ => status(StatusCode::NOT_FOUND),
+    };
+
+    Ok(response)
+}
+
+fn status(code: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = code;
+    response
+}
+
+fn json(code: StatusCode, value: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(value.to_string()));
+    *response.status_mut() = code;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn optional(value: Option<Value>) -> Response<Body> {
+    match value {
+        Some(value) => json(StatusCode::OK, &value),
+        None => status(StatusCode::NOT_FOUND),
+    }
+}
+
+fn metrics(registry: &Registry) -> Response<Body> {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    if let Err(err) = encoder.encode(&registry.gather(), &mut buffer) {
+        log::warn!("Failed to encode metrics: {err}");
+        return status(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let mut response = Response::new(Body::from(buffer));
+    if let Ok(value) = HeaderValue::from_str(encoder.format_type()) {
+        response.headers_mut().insert(CONTENT_TYPE, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::task::ShutdownTrigger;
+    use crate::health::{BoxedHealthChecked, HealthCheckError, HealthChecked};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    struct Dead;
+
+    #[async_trait]
+    impl HealthChecked for Dead {
+        async fn is_alive(&self) -> Result<(), HealthCheckError> {
+            HealthCheckError::nok("dead")
+        }
+    }
+
+    fn server(checker: HealthChecker, registry: Option<Registry>) -> HealthServer {
+        let config = HealthServerConfig {
+            bind_addr: "127.0.0.1:0".into(),
+            ..Default::default()
+        };
+        HealthServer::new(config, checker, registry)
+    }
+
+    /// Run the server, calling the test with its base URL, and stop it afterwards.
+    async fn with_server<F, Fut>(server: HealthServer, test: F)
+    where
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let trigger = ShutdownTrigger::new();
+        let server = server.shutdown_token(trigger.token());
+
+        let incoming = server.bind().unwrap();
+        let url = format!("http://{}", incoming.local_addr());
+
+        let test = async {
+            test(url).await;
+            trigger.trigger();
+        };
+
+        let (result, ()) = tokio::time::timeout(
+            Duration::from_secs(10),
+            futures_util::future::join(server.serve(incoming), test),
+        )
+        .await
+        .expect("Server must stop once triggered");
+        result.unwrap();
+    }
+
+    async fn get(url: &str) -> StatusCode {
+        let response = reqwest::get(url).await.unwrap();
+        StatusCode::from_u16(response.status().as_u16()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_endpoints() {
+        let checker = HealthChecker::default();
+
+        with_server(server(checker, Some(Registry::new())), |url| async move {
+            assert_eq!(get(&format!("{url}/readiness")).await, StatusCode::OK);
+            assert_eq!(get(&format!("{url}/liveness")).await, StatusCode::OK);
+            assert_eq!(get(&format!("{url}/metrics")).await, StatusCode::OK);
+            assert_eq!(get(&format!("{url}/unknown")).await, StatusCode::NOT_FOUND);
+
+            let response = reqwest::Client::new()
+                .post(format!("{url}/readiness"))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status().as_u16(), 405);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_failing() {
+        let checker = HealthChecker::default().starting();
+        checker.push(Dead.boxed());
+
+        with_server(server(checker, None), |url| async move {
+            // not started yet
+            assert_eq!(
+                get(&format!("{url}/readiness")).await,
+                StatusCode::SERVICE_UNAVAILABLE
+            );
+            assert_eq!(
+                get(&format!("{url}/liveness")).await,
+                StatusCode::SERVICE_UNAVAILABLE
+            );
+            // no registry
+            assert_eq!(get(&format!("{url}/metrics")).await, StatusCode::NOT_FOUND);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let trigger = ShutdownTrigger::new();
+        let server = server(HealthChecker::default(), None).shutdown_token(trigger.token());
+
+        trigger.trigger();
+        tokio::time::timeout(Duration::from_secs(10), server.run())
+            .await
+            .expect("Server must stop once triggered")
+            .unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+use super::Outcome;
+use crate::health::Criticality;
+use prometheus::{HistogramOpts, HistogramVec, IntGaugeVec, Opts, Registry};
+
+/// Metrics, recording the outcome of individual health checks.
+#[derive(Clone)]
+pub(crate) struct HealthMetrics {
+    status: IntGaugeVec,
+    duration: HistogramVec,
+}
+
+impl HealthMetrics {
+    pub(crate) fn new(registry: &Registry) -> prometheus::Result<Self> {
+        let status = IntGaugeVec::new(
+            Opts::new(
+                "health_check_status",
+                "The status of the last run of the check, 1 if it succeeded, 0 if it failed",
+            ),
+            &["check", "probe", "criticality"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "health_check_duration_seconds",
+                "The time it took to run the check",
+            ),
+            &["check", "probe"],
+        )?;
+
+        registry.register(Box::new(status.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+
+        Ok(Self { status, duration })
+    }
+
+    /// Record the outcome of a check.
+    pub(crate) fn record(&self, probe: &str, outcome: &Outcome) {
+        let criticality = match outcome.criticality {
+            Criticality::Critical => "critical",
+            Criticality::NonCritical => "nonCritical",
+            Criticality::LivenessOnly => "livenessOnly",
+        };
+        self.status
+            .with_label_values(&[&outcome.name, probe, criticality])
+            .set(outcome.result.is_ok() as i64);
+        self.duration
+            .with_label_values(&[&outcome.name, probe])
+            .observe(outcome.duration.as_secs_f64());
+    }
+}
@@ -1,20 +1,41 @@
 #[cfg(feature = "actix")]
 mod actix;
+#[cfg(not(feature = "actix"))]
+mod hyper;
+mod metrics;
+mod report;
 
+#[cfg(not(feature = "actix"))]
+pub use self::hyper::HealthServer;
 #[cfg(feature = "actix")]
 pub use actix::HealthServer;
+pub use report::*;
 
-use crate::health::{HealthCheckError, HealthChecked};
-use futures_util::stream::StreamExt;
-use serde::Deserialize;
+use crate::{
+    app::RuntimeConfig,
+    core::{
+        config::{ConfigReference, Reference},
+        info::ComponentInformation,
+        task::{ShutdownToken, TaskMonitor},
+    },
+    health::{Criticality, HealthCheckError, HealthChecked},
+};
+use chrono::{DateTime, Utc};
+use humantime::format_duration;
+use metrics::HealthMetrics;
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::future::Future;
-use std::sync::Arc;
-use tokio::runtime::Handle;
-use tokio::sync::RwLock;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock, Weak,
+};
+use std::time::Duration;
+use tokio::time::Instant;
 use tracing::instrument;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct HealthServerConfig {
     #[serde(default)]
     pub enabled: bool,
@@ -22,9 +43,20 @@ pub struct HealthServerConfig {
     pub bind_addr: String,
     #[serde(default = "defaults::workers")]
     pub workers: usize,
+    /// The maximum time a single check may take, before it is considered failed.
+    #[serde(default = "defaults::check_timeout", with = "humantime_serde")]
+    pub check_timeout: Duration,
+    /// Run the checks in the background using this interval, serving the cached results.
+    #[serde(default, with = "humantime_serde")]
+    pub refresh_interval: Option<Duration>,
+    /// The maximum age of the cached results, before the readiness check fails.
+    #[serde(default = "defaults::max_age", with = "humantime_serde")]
+    pub max_age: Duration,
 }
 
 mod defaults {
+    use std::time::Duration;
+
     #[inline]
     pub fn bind_addr() -> String {
         "[::1]:9090".into()
@@ -34,6 +66,16 @@ mod defaults {
     pub fn workers() -> usize {
         1
     }
+
+    #[inline]
+    pub fn check_timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+
+    #[inline]
+    pub fn max_age() -> Duration {
+        Duration::from_secs(60)
+    }
 }
 
 impl Default for HealthServerConfig {
@@ -42,42 +84,461 @@ impl Default for HealthServerConfig {
             enabled: false,
             bind_addr: defaults::bind_addr(),
             workers: defaults::workers(),
+            check_timeout: defaults::check_timeout(),
+            refresh_interval: None,
+            max_age: defaults::max_age(),
         }
     }
 }
 
+impl ConfigReference for HealthServerConfig {
+    fn describe(r: &mut Reference) {
+        r.with_default("enabled", "boolean", false, "Enable the health server");
+        r.with_default(
+            "bind_addr",
+            "string",
+            defaults::bind_addr(),
+            "The address to bind the health server to",
+        );
+        r.with_default(
+            "workers",
+            "integer",
+            defaults::workers(),
+            "The number of workers of the health server (only used with the 'actix' feature)",
+        );
+        r.with_default(
+            "check_timeout",
+            "duration",
+            format_duration(defaults::check_timeout()),
+            "The maximum time a single health check may take",
+        );
+        r.optional(
+            "refresh_interval",
+            "duration",
+            "Run the checks in the background using this interval, instead of on every probe",
+        );
+        r.with_default(
+            "max_age",
+            "duration",
+            format_duration(defaults::max_age()),
+            "The maximum age of results from background checks, before failing readiness",
+        );
+    }
+}
+
 /// Internal handling of health checking.
-#[derive(Clone, Default)]
+///
+/// When gated by [`HealthChecker::starting`], the readiness check fails until the application is
+/// marked as started, using [`HealthChecker::started`]. [`crate::app::Main`] does this
+/// automatically, once all started hooks completed.
+///
+/// All checks are run concurrently, a check which doesn't complete within the timeout is
+/// considered failed.
+///
+/// Checks are registered synchronously, and are visible to the next probe. They are run in the
+/// order they were registered.
+///
+/// When using a cache, checks are only run by [`HealthChecker::refresh`], and the probes report
+/// the last results. If these results are older than the maximum age, the readiness check fails.
+///
+/// Once metrics are registered, using [`HealthChecker::register_metrics`], the status and
+/// duration of every run of a check gets recorded.
+#[derive(Clone)]
 pub struct HealthChecker {
-    checks: Arc<RwLock<Vec<Box<dyn HealthChecked>>>>,
+    checks: Arc<RwLock<Checks>>,
+    started: Arc<AtomicBool>,
+    shutting_down: Arc<AtomicBool>,
+    timeout: Duration,
+    cache: Option<Arc<RwLock<Cache>>>,
+    metrics: Arc<RwLock<Option<HealthMetrics>>>,
+}
+
+/// The registered checks, with their ID.
+#[derive(Default)]
+struct Checks {
+    next_id: u64,
+    checks: Vec<(u64, Arc<dyn HealthChecked>)>,
+}
+
+impl Checks {
+    fn push(&mut self, check: Arc<dyn HealthChecked>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.checks.push((id, check));
+        id
+    }
+}
+
+/// A handle to a registered check, allowing to remove it again.
+///
+/// Dropping the handle keeps the check registered.
+pub struct CheckHandle {
+    id: u64,
+    checks: Weak<RwLock<Checks>>,
+}
+
+impl core::fmt::Debug for CheckHandle {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CheckHandle")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CheckHandle {
+    /// Create a handle, which isn't connected to any check.
+    pub(crate) fn detached() -> Self {
+        Self {
+            id: 0,
+            checks: Weak::new(),
+        }
+    }
+
+    /// Remove the check, returns `false` if it was already removed.
+    pub fn remove(self) -> bool {
+        match self.checks.upgrade() {
+            Some(checks) => {
+                let mut checks = checks.write().unwrap();
+                let len = checks.checks.len();
+                checks.checks.retain(|(id, _)| *id != self.id);
+                checks.checks.len() != len
+            }
+            None => false,
+        }
+    }
+}
+
+/// The results of the last background run.
+struct Cache {
+    max_age: Duration,
+    last: Option<(Instant, Vec<CheckResult>, Vec<CheckResult>)>,
+}
+
+impl Default for HealthChecker {
+    fn default() -> Self {
+        Self::new(defaults::check_timeout())
+    }
+}
+
+/// The outcome of running a single check.
+struct Outcome {
+    name: String,
+    criticality: Criticality,
+    duration: Duration,
+    result: Result<(), HealthCheckError>,
+}
+
+impl From<&CheckResult> for Outcome {
+    fn from(result: &CheckResult) -> Self {
+        Self {
+            name: result.name.clone(),
+            criticality: result.criticality,
+            duration: result.duration,
+            result: match (result.success, &result.message) {
+                (true, _) => Ok(()),
+                (false, message) => HealthCheckError::nok(message.clone().unwrap_or_default()),
+            },
+        }
+    }
+}
+
+impl Outcome {
+    /// A failed, critical, outcome of an internal check.
+    fn failed(name: &str, reason: &str) -> Self {
+        Self {
+            name: name.into(),
+            criticality: Criticality::Critical,
+            duration: Duration::ZERO,
+            result: HealthCheckError::nok(reason),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Probe {
+    Ready,
+    Alive,
+}
+
+impl Probe {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Ready => "readiness",
+            Self::Alive => "liveness",
+        }
+    }
 }
 
 impl HealthChecker {
+    /// Create a new instance, using the timeout for running a single check.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            checks: Default::default(),
+            started: Arc::new(AtomicBool::new(true)),
+            shutting_down: Default::default(),
+            timeout,
+            cache: None,
+            metrics: Default::default(),
+        }
+    }
+
+    /// Gate the readiness check on the startup, failing it until [`Self::started`] is called.
+    pub fn starting(self) -> Self {
+        self.started.store(false, Ordering::Relaxed);
+        self
+    }
+
+    /// Register the metrics of the checks with the registry.
+    ///
+    /// The metrics are shared between all clones of this instance.
+    pub fn register_metrics(&self, registry: &Registry) -> prometheus::Result<()> {
+        let metrics = HealthMetrics::new(registry)?;
+        *self.metrics.write().unwrap() = Some(metrics);
+        Ok(())
+    }
+
+    /// Serve the results of the last call to [`Self::refresh`] instead of running the checks
+    /// on every probe.
+    pub fn cached(mut self, max_age: Duration) -> Self {
+        self.cache = Some(Arc::new(RwLock::new(Cache {
+            max_age,
+            last: None,
+        })));
+        self
+    }
+
+    /// Run all checks, storing the results in the cache.
+    ///
+    /// The checks are run in a single pass, each check providing its results for both probes.
+    pub async fn refresh(&self) {
+        if let Some(cache) = &self.cache {
+            let checks = self.snapshot();
+
+            let outcomes = futures_util::future::join_all(checks.iter().map(|check| async move {
+                let ready = async {
+                    match check.criticality() {
+                        Criticality::LivenessOnly => None,
+                        _ => Some(self.run_check(check.as_ref(), Probe::Ready).await),
+                    }
+                };
+                let alive = self.run_check(check.as_ref(), Probe::Alive);
+                futures_util::future::join(ready, alive).await
+            }))
+            .await;
+
+            let (ready, alive): (Vec<_>, Vec<_>) = outcomes.into_iter().unzip();
+            let ready: Vec<_> = ready.into_iter().flatten().collect();
+            self.record(Probe::Ready, &ready);
+            self.record(Probe::Alive, &alive);
+
+            cache.write().unwrap().last = Some((Instant::now(), reports(ready), reports(alive)));
+        }
+    }
+
+    /// Periodically refresh the cache, until the shutdown is triggered.
+    pub async fn run_refresh(
+        self,
+        interval: Duration,
+        shutdown: ShutdownToken,
+    ) -> anyhow::Result<()> {
+        loop {
+            self.refresh().await;
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = shutdown.triggered() => return Ok(()),
+            }
+        }
+    }
+
+    /// Get the cached results of a probe, with their age.
+    ///
+    /// Returns [`None`] if no cache is being used.
+    fn cached_outcomes(&self, probe: Probe) -> Option<(Vec<Outcome>, Option<Duration>)> {
+        let cache = self.cache.as_ref()?.read().unwrap();
+
+        let (age, results) = match &cache.last {
+            Some((time, ready, alive)) => (
+                Some(time.elapsed()),
+                match probe {
+                    Probe::Ready => ready,
+                    Probe::Alive => alive,
+                },
+            ),
+            None => {
+                let outcomes = match probe {
+                    Probe::Ready => vec![Outcome::failed("cache", "No results yet")],
+                    Probe::Alive => vec![],
+                };
+                return Some((outcomes, None));
+            }
+        };
+
+        let mut outcomes: Vec<_> = results.iter().map(Outcome::from).collect();
+
+        if let (Probe::Ready, Some(age)) = (probe, age) {
+            if age > cache.max_age {
+                outcomes.push(Outcome::failed(
+                    "cache",
+                    &format!("Results are stale ({} old)", format_duration(age)),
+                ));
+            }
+        }
+
+        Some((outcomes, age))
+    }
+
+    /// Run the checks of a probe, or use the cached results.
+    async fn outcomes(&self, probe: Probe) -> (Vec<Outcome>, Option<Duration>) {
+        match self.cached_outcomes(probe) {
+            Some(cached) => cached,
+            None => (self.run(probe).await, None),
+        }
+    }
+
     #[instrument(level = "trace", skip(self), ret)]
     pub async fn is_ready(&self) -> Vec<Result<(), HealthCheckError>> {
-        futures_util::stream::iter(self.checks.read().await.iter())
-            .then(|check| check.is_ready())
-            .collect()
-            .await
+        results(self.ready().await.0)
+    }
+
+    /// Run the readiness checks, reporting the result of each check.
+    pub async fn ready_report(&self) -> HealthReport {
+        let (outcomes, age) = self.ready().await;
+        report(outcomes, age)
+    }
+
+    async fn ready(&self) -> (Vec<Outcome>, Option<Duration>) {
+        let (mut result, age) = self.outcomes(Probe::Ready).await;
+
+        result.extend(self.startup());
+
+        if self.is_shutting_down() {
+            result.push(Outcome::failed("shutdown", "Shutting down"));
+        }
+
+        (result, age)
+    }
+
+    /// Check if the application completed its startup.
+    pub fn is_started_up(&self) -> Vec<Result<(), HealthCheckError>> {
+        results(self.startup())
+    }
+
+    /// Check if the application completed its startup, reporting the result.
+    pub fn startup_report(&self) -> HealthReport {
+        report(self.startup(), None)
+    }
+
+    fn startup(&self) -> Vec<Outcome> {
+        if self.is_started() {
+            vec![]
+        } else {
+            vec![Outcome::failed("startup", "Starting up")]
+        }
+    }
+
+    /// Mark the application as started, allowing the readiness checks to succeed.
+    pub fn started(&self) {
+        self.started.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
+    }
+
+    /// Mark the application as shutting down, failing all further readiness checks.
+    pub fn shutting_down(&self) {
+        self.shutting_down.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Relaxed)
     }
 
     #[instrument(level = "trace", skip(self), ret)]
     pub async fn is_alive(&self) -> Vec<Result<(), HealthCheckError>> {
-        futures_util::stream::iter(self.checks.read().await.iter())
-            .then(|check| check.is_alive())
-            .collect()
+        results(self.outcomes(Probe::Alive).await.0)
+    }
+
+    /// Run the liveness checks, reporting the result of each check.
+    pub async fn alive_report(&self) -> HealthReport {
+        let (outcomes, age) = self.outcomes(Probe::Alive).await;
+        report(outcomes, age)
+    }
+
+    async fn run(&self, probe: Probe) -> Vec<Outcome> {
+        let checks = self.snapshot();
+
+        let checks = checks.iter().filter(|check| match probe {
+            Probe::Ready => check.criticality() != Criticality::LivenessOnly,
+            Probe::Alive => true,
+        });
+
+        let outcomes = futures_util::future::join_all(
+            checks.map(|check| self.run_check(check.as_ref(), probe)),
+        )
+        .await;
+
+        self.record(probe, &outcomes);
+
+        outcomes
+    }
+
+    /// Run a single probe of a check, failing it if it doesn't complete within the timeout.
+    async fn run_check(&self, check: &dyn HealthChecked, probe: Probe) -> Outcome {
+        let timeout = self.timeout;
+        let start = Instant::now();
+        let result = match probe {
+            Probe::Ready => check.is_ready(),
+            Probe::Alive => check.is_alive(),
+        };
+        let result = tokio::time::timeout(timeout, result)
             .await
+            .unwrap_or_else(|_| {
+                HealthCheckError::nok(format!("Timed out after {}", format_duration(timeout)))
+            });
+        Outcome {
+            name: check.name(),
+            criticality: check.criticality(),
+            duration: start.elapsed(),
+            result,
+        }
     }
 
-    pub fn push<C>(&self, check: C)
+    /// Record the outcomes of a probe in the metrics, if registered.
+    fn record(&self, probe: Probe, outcomes: &[Outcome]) {
+        if let Some(metrics) = &*self.metrics.read().unwrap() {
+            for outcome in outcomes {
+                metrics.record(probe.label(), outcome);
+            }
+        }
+    }
+
+    /// Get the currently registered checks, so that they can be run without holding the lock.
+    fn snapshot(&self) -> Vec<Arc<dyn HealthChecked>> {
+        self.checks
+            .read()
+            .unwrap()
+            .checks
+            .iter()
+            .map(|(_, check)| check.clone())
+            .collect()
+    }
+
+    /// The names of all registered checks.
+    pub fn names(&self) -> Vec<String> {
+        self.snapshot().iter().map(|check| check.name()).collect()
+    }
+
+    /// Register a check, which is visible to the next probe.
+    pub fn push<C>(&self, check: C) -> CheckHandle
     where
         C: Into<Box<dyn HealthChecked + 'static>>,
     {
-        let check = check.into();
-        let checks = self.checks.clone();
-        Handle::current().spawn(async move {
-            checks.write().await.push(check);
-        });
+        let id = self.checks.write().unwrap().push(check.into().into());
+        CheckHandle {
+            id,
+            checks: Arc::downgrade(&self.checks),
+        }
     }
 }
 
@@ -86,42 +547,341 @@ where
     C: HealthChecked + 'static,
 {
     fn extend<T: IntoIterator<Item = C>>(&mut self, iter: T) {
-        // collect first, so that we can send/spawn it
-        let iter: Vec<_> = iter
-            .into_iter()
-            .map(|c| Box::new(c) as Box<dyn HealthChecked>)
-            .collect();
-        let checks = self.checks.clone();
-        Handle::current().spawn(async move {
-            checks.write().await.extend(iter);
-        });
+        let mut checks = self.checks.write().unwrap();
+        for check in iter {
+            checks.push(Arc::new(check));
+        }
     }
 }
 
 impl Extend<Box<dyn HealthChecked>> for HealthChecker {
     fn extend<T: IntoIterator<Item = Box<dyn HealthChecked + 'static>>>(&mut self, iter: T) {
-        // collect first, so that we can send/spawn it
-        let iter: Vec<_> = iter.into_iter().collect();
-        let checks = self.checks.clone();
-        Handle::current().spawn(async move {
-            checks.write().await.extend(iter);
-        });
+        let mut checks = self.checks.write().unwrap();
+        for check in iter {
+            checks.push(check.into());
+        }
     }
 }
 
-#[allow(unused)]
-async fn run_checks<F, Fut>(checker: Arc<HealthChecker>, f: F) -> (http::StatusCode, Value)
+/// The results affecting the probe, ignoring non-critical checks.
+fn results(outcomes: Vec<Outcome>) -> Vec<Result<(), HealthCheckError>> {
+    outcomes
+        .into_iter()
+        .filter(|outcome| outcome.criticality != Criticality::NonCritical)
+        .map(|outcome| outcome.result)
+        .collect()
+}
+
+fn reports(outcomes: Vec<Outcome>) -> Vec<CheckResult> {
+    outcomes
+        .iter()
+        .map(|outcome| {
+            CheckResult::new(
+                outcome.name.as_str(),
+                outcome.criticality,
+                outcome.duration,
+                &outcome.result,
+            )
+        })
+        .collect()
+}
+
+fn report(outcomes: Vec<Outcome>, age: Option<Duration>) -> HealthReport {
+    HealthReport {
+        age,
+        ..reports(outcomes).into_iter().collect()
+    }
+}
+
+/// Run checks, returning the status code and the report, only containing the individual checks
+/// when being verbose.
+async fn run_checks<F, Fut>(
+    checker: Arc<HealthChecker>,
+    verbose: bool,
+    f: F,
+) -> (http::StatusCode, Value)
 where
     F: FnOnce(Arc<HealthChecker>) -> Fut,
-    Fut: Future<Output = Vec<Result<(), HealthCheckError>>>,
+    Fut: Future<Output = HealthReport>,
 {
-    let result: Result<Vec<()>, _> = f(checker).await.into_iter().collect();
-
-    match result {
-        Ok(_) => (http::StatusCode::OK, json!({ "success": true})),
-        Err(_) => (
-            http::StatusCode::SERVICE_UNAVAILABLE,
-            json!({"success": false}),
-        ),
+    let report = f(checker).await;
+
+    let code = match report.success {
+        true => http::StatusCode::OK,
+        false => http::StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (code, report.to_json(verbose))
+}
+
+/// Information about the running instance, provided by the health server.
+#[derive(Clone, Debug)]
+pub(crate) struct InstanceInfo {
+    pub component: Option<ComponentInformation>,
+    pub runtime: Option<RuntimeConfig>,
+    pub started: DateTime<Utc>,
+    pub tasks: Option<TaskMonitor>,
+}
+
+impl Default for InstanceInfo {
+    fn default() -> Self {
+        Self {
+            component: None,
+            runtime: None,
+            started: Utc::now(),
+            tasks: None,
+        }
+    }
+}
+
+impl InstanceInfo {
+    /// The version information, if available.
+    fn version(&self) -> Option<Value> {
+        self.component
+            .as_ref()
+            .and_then(|component| serde_json::to_value(component).ok())
+    }
+
+    /// The status of all tasks, if available.
+    fn tasks(&self) -> Option<Value> {
+        self.tasks
+            .as_ref()
+            .and_then(|tasks| serde_json::to_value(tasks.tasks()).ok())
+    }
+
+    /// The full information, including the names of all health checks.
+    async fn info(&self, checker: &HealthChecker) -> Value {
+        let uptime = (Utc::now() - self.started)
+            .to_std()
+            .map(|uptime| Duration::from_secs(uptime.as_secs()))
+            .unwrap_or_default();
+
+        json!({
+            "component": self.component,
+            "startTime": self.started.to_rfc3339(),
+            "uptime": format_duration(uptime).to_string(),
+            "uptimeSeconds": uptime.as_secs(),
+            "runtime": self.runtime,
+            "checks": checker.names(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::assert_reference;
+    use crate::health::BoxedHealthChecked;
+    use crate::{component, project};
+
+    struct Check;
+
+    impl HealthChecked for Check {}
+
+    #[tokio::test]
+    async fn test_info() {
+        project!(PROJECT: "Test");
+
+        let checker = HealthChecker::default();
+        checker.push(Check.boxed());
+
+        let info = InstanceInfo {
+            component: Some(component!(PROJECT)),
+            runtime: Some(Default::default()),
+            started: Utc::now() - chrono::Duration::seconds(90),
+            tasks: None,
+        };
+
+        let value = info.info(&checker).await;
+
+        assert_eq!(value["component"]["project"]["name"], "Test");
+        assert_eq!(value["uptime"], "1m 30s");
+        assert_eq!(value["runtime"]["health"]["enabled"], false);
+        assert_eq!(
+            value["checks"],
+            json!(["drogue_bazaar::app::health::run::test::Check"])
+        );
+        assert_eq!(info.version().unwrap()["name"], value["component"]["name"]);
+    }
+
+    #[tokio::test]
+    async fn test_started() {
+        let checker = HealthChecker::default();
+        assert!(checker.is_ready().await.iter().all(|r| r.is_ok()));
+
+        let checker = checker.starting();
+        assert!(checker.is_ready().await.iter().any(|r| r.is_err()));
+        assert!(!checker.is_started_up().is_empty());
+
+        checker.started();
+        assert!(checker.is_ready().await.iter().all(|r| r.is_ok()));
+        assert!(checker.is_started_up().is_empty());
+
+        checker.shutting_down();
+        assert!(checker.is_ready().await.iter().any(|r| r.is_err()));
+    }
+
+    #[tokio::test]
+    async fn test_report() {
+        let checker = HealthChecker::default().starting();
+        checker.push(Check.boxed());
+
+        let report = checker.ready_report().await;
+        assert!(!report.success);
+        let names: Vec<_> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["drogue_bazaar::app::health::run::test::Check", "startup"]
+        );
+        assert_eq!(report.checks[1].message.as_deref(), Some("Starting up"));
+
+        assert!(checker.alive_report().await.success);
+    }
+
+    struct Hanging;
+
+    #[async_trait::async_trait]
+    impl HealthChecked for Hanging {
+        async fn is_alive(&self) -> Result<(), HealthCheckError> {
+            futures_util::future::pending::<()>().await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let checker = HealthChecker::new(Duration::from_millis(10));
+        checker.push(Hanging.boxed());
+        checker.push(Check.boxed());
+
+        let report = checker.alive_report().await;
+
+        assert!(!report.success);
+        assert!(!report.checks[0].success);
+        assert_eq!(
+            report.checks[0].message.as_deref(),
+            Some("Timed out after 10ms")
+        );
+        assert!(report.checks[1].success);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cached() {
+        let checker = HealthChecker::default().cached(Duration::from_millis(50));
+        checker.push(Check.boxed());
+        checker.started();
+
+        let report = checker.ready_report().await;
+        assert!(!report.success);
+        assert_eq!(report.checks[0].message.as_deref(), Some("No results yet"));
+        assert!(checker.alive_report().await.success);
+
+        checker.refresh().await;
+
+        let report = checker.ready_report().await;
+        assert!(report.success);
+        assert_eq!(report.checks.len(), 1);
+        assert!(report.age.is_some());
+
+        tokio::time::advance(Duration::from_millis(60)).await;
+
+        let report = checker.ready_report().await;
+        assert!(!report.success);
+        assert_eq!(report.checks[1].name, "cache");
+        assert!(checker.alive_report().await.success);
+    }
+
+    #[derive(Default)]
+    struct Counting {
+        ready: std::sync::atomic::AtomicUsize,
+        alive: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl HealthChecked for Arc<Counting> {
+        async fn is_ready(&self) -> Result<(), HealthCheckError> {
+            self.ready.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        async fn is_alive(&self) -> Result<(), HealthCheckError> {
+            self.alive.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let checker = HealthChecker::default().cached(Duration::from_secs(60));
+        let counting = Arc::new(Counting::default());
+        checker.push(counting.clone().boxed());
+
+        checker.refresh().await;
+        assert_eq!(counting.ready.load(Ordering::Relaxed), 1);
+        assert_eq!(counting.alive.load(Ordering::Relaxed), 1);
+
+        // probes are served from the cache
+        assert!(checker.ready_report().await.success);
+        assert!(checker.alive_report().await.success);
+        assert_eq!(counting.ready.load(Ordering::Relaxed), 1);
+        assert_eq!(counting.alive.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_register() {
+        let checker = HealthChecker::default();
+
+        let first = checker.push(Check.boxed());
+        checker.push(Hanging.boxed());
+        assert_eq!(
+            checker.names(),
+            vec![
+                "drogue_bazaar::app::health::run::test::Check",
+                "drogue_bazaar::app::health::run::test::Hanging"
+            ]
+        );
+
+        assert!(first.remove());
+        assert_eq!(
+            checker.names(),
+            vec!["drogue_bazaar::app::health::run::test::Hanging"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics() {
+        let registry = Registry::new();
+        let checker = HealthChecker::default();
+        checker.register_metrics(&registry).unwrap();
+        checker.push(Check.boxed());
+
+        checker.started();
+        assert!(checker.ready_report().await.success);
+
+        let families = registry.gather();
+        let status = families
+            .iter()
+            .find(|family| family.get_name() == "health_check_status")
+            .unwrap();
+        let metric = &status.get_metric()[0];
+        assert_eq!(metric.get_gauge().get_value(), 1.0);
+        assert!(metric
+            .get_label()
+            .iter()
+            .any(|label| label.get_name() == "probe" && label.get_value() == "readiness"));
+
+        let duration = families
+            .iter()
+            .find(|family| family.get_name() == "health_check_duration_seconds")
+            .unwrap();
+        assert_eq!(
+            duration.get_metric()[0].get_histogram().get_sample_count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_reference() {
+        assert_reference::<HealthServerConfig>();
     }
 }
@@ -0,0 +1,185 @@
+use crate::health::{Criticality, HealthCheckError};
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// The result of a single health check.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    /// The name of the check.
+    pub name: String,
+    pub criticality: Criticality,
+    pub success: bool,
+    /// The time it took to run the check.
+    #[serde(rename = "durationMs", serialize_with = "millis")]
+    pub duration: Duration,
+    /// The reason the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+fn millis<S: Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(duration.as_millis() as u64)
+}
+
+fn optional_millis<S: Serializer>(duration: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => millis(duration, s),
+        None => s.serialize_none(),
+    }
+}
+
+impl CheckResult {
+    pub fn new<N: Into<String>>(
+        name: N,
+        criticality: Criticality,
+        duration: Duration,
+        result: &Result<(), HealthCheckError>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            criticality,
+            success: result.is_ok(),
+            duration,
+            message: result.as_ref().err().map(|err| match err {
+                HealthCheckError::NotOk(reason) => reason.clone(),
+                err => err.to_string(),
+            }),
+        }
+    }
+}
+
+/// The overall status of a set of health checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    /// All checks succeeded.
+    Ok,
+    /// Only non-critical checks failed.
+    Degraded,
+    /// At least one critical check failed.
+    Failed,
+}
+
+/// The report of running a set of health checks.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// If no critical check failed.
+    pub success: bool,
+    pub status: HealthStatus,
+    pub checks: Vec<CheckResult>,
+    /// The age of the results, when being served from the cache.
+    #[serde(
+        rename = "ageMs",
+        serialize_with = "optional_millis",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub age: Option<Duration>,
+}
+
+impl FromIterator<CheckResult> for HealthReport {
+    fn from_iter<T: IntoIterator<Item = CheckResult>>(iter: T) -> Self {
+        let checks: Vec<_> = iter.into_iter().collect();
+
+        let mut status = HealthStatus::Ok;
+        for check in checks.iter().filter(|check| !check.success) {
+            match check.criticality {
+                Criticality::NonCritical => status = HealthStatus::Degraded,
+                _ => {
+                    status = HealthStatus::Failed;
+                    break;
+                }
+            }
+        }
+
+        Self {
+            success: status != HealthStatus::Failed,
+            status,
+            checks,
+            age: None,
+        }
+    }
+}
+
+impl HealthReport {
+    /// Convert into JSON, only containing the individual checks when being verbose.
+    pub fn to_json(&self, verbose: bool) -> Value {
+        match verbose {
+            true => serde_json::to_value(self).unwrap_or_else(|_| json!({"success": false})),
+            false => json!({"success": self.success}),
+        }
+    }
+}
+
+/// Check if the query string requests a verbose report (`?verbose` or `?verbose=true`).
+pub(crate) fn is_verbose(query: &str) -> bool {
+    query
+        .split('&')
+        .any(|param| matches!(param, "verbose" | "verbose=true" | "verbose=1"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        let report: HealthReport = vec![
+            CheckResult::new(
+                "a",
+                Criticality::Critical,
+                Duration::from_millis(5),
+                &Ok(()),
+            ),
+            CheckResult::new(
+                "b",
+                Criticality::Critical,
+                Duration::ZERO,
+                &HealthCheckError::nok("Broken"),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(report.to_json(false), json!({"success": false}));
+        assert_eq!(
+            report.to_json(true),
+            json!({
+                "success": false,
+                "status": "failed",
+                "checks": [
+                    {"name": "a", "criticality": "critical", "success": true, "durationMs": 5},
+                    {"name": "b", "criticality": "critical", "success": false, "durationMs": 0, "message": "Broken"},
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_degraded() {
+        let report: HealthReport = vec![
+            CheckResult::new("a", Criticality::Critical, Duration::ZERO, &Ok(())),
+            CheckResult::new(
+                "b",
+                Criticality::NonCritical,
+                Duration::ZERO,
+                &HealthCheckError::nok("Broken"),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert!(report.success);
+        assert_eq!(report.status, HealthStatus::Degraded);
+    }
+
+    #[test]
+    fn test_verbose() {
+        assert!(is_verbose("verbose"));
+        assert!(is_verbose("foo=bar&verbose=true"));
+        assert!(!is_verbose(""));
+        assert!(!is_verbose("verbose=false"));
+    }
+}
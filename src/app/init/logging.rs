@@ -0,0 +1,141 @@
+use crate::core::config::{ConfigReference, Reference};
+use log::{Log, Metadata, Record};
+use std::sync::{Arc, RwLock};
+
+/// Configuration of the logging, when tracing is disabled.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct LogConfig {
+    /// The log filter, using the syntax of `RUST_LOG`, which it replaces.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+impl ConfigReference for LogConfig {
+    fn describe(r: &mut Reference) {
+        r.optional(
+            "filter",
+            "string",
+            "The log filter, replacing `RUST_LOG`. Changes are applied when loading the configuration from files",
+        );
+    }
+}
+
+/// A handle, allowing to change the filter of the logger.
+#[derive(Clone)]
+pub struct LogFilterHandle {
+    logger: Arc<RwLock<env_logger::Logger>>,
+}
+
+impl core::fmt::Debug for LogFilterHandle {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LogFilterHandle").finish_non_exhaustive()
+    }
+}
+
+impl LogFilterHandle {
+    /// Set the filter, using the syntax of `RUST_LOG`.
+    ///
+    /// If no filter is provided, the value of `RUST_LOG` is used.
+    pub fn set_filter(&self, filter: Option<&str>) {
+        let logger = build(filter);
+        log::set_max_level(logger.filter());
+        if let Ok(mut current) = self.logger.write() {
+            *current = logger;
+        }
+    }
+}
+
+/// A logger, delegating to an `env_logger`, which can be replaced.
+struct ReloadableLogger {
+    logger: Arc<RwLock<env_logger::Logger>>,
+}
+
+impl ReloadableLogger {
+    fn new(filter: Option<&str>) -> (Self, LogFilterHandle) {
+        let logger = Arc::new(RwLock::new(build(filter)));
+        (
+            Self {
+                logger: logger.clone(),
+            },
+            LogFilterHandle { logger },
+        )
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger
+            .read()
+            .map(|logger| logger.enabled(metadata))
+            .unwrap_or_default()
+    }
+
+    fn log(&self, record: &Record) {
+        if let Ok(logger) = self.logger.read() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(logger) = self.logger.read() {
+            logger.flush();
+        }
+    }
+}
+
+fn build(filter: Option<&str>) -> env_logger::Logger {
+    let mut builder = match filter {
+        Some(filter) => {
+            let mut builder = env_logger::Builder::new();
+            builder.parse_filters(filter);
+            builder
+        }
+        None => env_logger::Builder::from_default_env(),
+    };
+    builder.format_timestamp_millis().build()
+}
+
+/// Install the logger, returning a handle to change its filter.
+///
+/// This will panic if a logger was already installed.
+pub(crate) fn init(filter: Option<&str>) -> LogFilterHandle {
+    let (logger, handle) = ReloadableLogger::new(filter);
+    let max_level = handle
+        .logger
+        .read()
+        .map(|logger| logger.filter())
+        .unwrap_or(log::LevelFilter::Error);
+
+    log::set_logger(Box::leak(Box::new(logger))).expect("Failed to install the logger");
+    log::set_max_level(max_level);
+
+    handle
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::assert_reference;
+    use log::Level;
+
+    #[test]
+    fn test_set_filter() {
+        let (logger, handle) = ReloadableLogger::new(Some("warn"));
+
+        let info = Metadata::builder().level(Level::Info).target("foo").build();
+        let warn = Metadata::builder().level(Level::Warn).target("foo").build();
+        assert!(!logger.enabled(&info));
+        assert!(logger.enabled(&warn));
+
+        handle.set_filter(Some("warn,foo=info"));
+        assert!(logger.enabled(&info));
+
+        let info = Metadata::builder().level(Level::Info).target("bar").build();
+        assert!(!logger.enabled(&info));
+    }
+
+    #[test]
+    fn test_reference() {
+        assert_reference::<LogConfig>();
+    }
+}
@@ -1,5 +1,7 @@
+mod logging;
 mod tracing;
 
+pub use self::logging::{LogConfig, LogFilterHandle};
 pub use self::tracing::Tracing;
 
 pub fn phase1(dotenv: bool) {
@@ -10,5 +12,12 @@ pub fn phase1(dotenv: bool) {
 }
 
 pub fn phase2(name: &str, tracing: Tracing) {
-    tracing::init_tracing(name, tracing);
+    phase2_with_log(name, tracing, &Default::default());
+}
+
+/// Like [`phase2`], but using a log configuration.
+///
+/// If tracing is disabled, a handle to change the log filter at runtime is returned.
+pub fn phase2_with_log(name: &str, tracing: Tracing, log: &LogConfig) -> Option<LogFilterHandle> {
+    tracing::init_tracing(name, tracing, log)
 }
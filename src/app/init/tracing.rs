@@ -1,4 +1,6 @@
-#[derive(Clone, Debug, serde::Deserialize)]
+use super::logging::{LogConfig, LogFilterHandle};
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Tracing {
     Disabled,
@@ -32,13 +34,16 @@ fn sampler() -> opentelemetry::sdk::trace::Sampler {
     }
 }
 
-pub fn init_tracing(name: &str, tracing: Tracing) {
+/// Initialize tracing, or logging if tracing is disabled.
+///
+/// A handle to the log filter is only returned when tracing is disabled, as tracing uses the
+/// `RUST_LOG` variable directly.
+pub fn init_tracing(name: &str, tracing: Tracing, log: &LogConfig) -> Option<LogFilterHandle> {
     match tracing {
-        Tracing::Disabled => {
-            init_no_tracing();
-        }
+        Tracing::Disabled => Some(init_no_tracing(log)),
         Tracing::Jaeger => {
             init_jaeger(name);
+            None
         }
     }
 }
@@ -70,7 +75,8 @@ pub fn init_jaeger(name: &str) {
         .init();
 }
 
-fn init_no_tracing() {
-    env_logger::builder().format_timestamp_millis().init();
+fn init_no_tracing(log: &LogConfig) -> LogFilterHandle {
+    let handle = super::logging::init(log.filter.as_deref());
     log::info!("No tracing subscriber is active, logging stays active");
+    handle
 }
@@ -7,4 +7,7 @@ pub mod init;
 /// Application run method support.
 pub mod run;
 
-pub use run::{Main, Runtime, RuntimeConfig, Startup, StartupExt, SubMain, SubMainSeed};
+pub use run::{
+    exit_code, Hook, Main, Runtime, RuntimeConfig, Startup, StartupExt, SubMain, SubMainSeed,
+    TaskFailed,
+};
@@ -0,0 +1,172 @@
+use futures_core::future::LocalBoxFuture;
+use futures_util::FutureExt;
+use humantime::format_duration;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::time::Duration;
+
+/// A hook, executed when the application reaches a point in its lifecycle.
+///
+/// Hooks are executed one after the other, ordered by their [`Hook::order`], and in the order
+/// they were added. Shutdown hooks with the same order are executed in the reverse order they
+/// were added, so that resources get released in the opposite order they were acquired.
+///
+/// ```
+/// use drogue_bazaar::app::{Hook, Startup, StartupExt};
+/// use std::time::Duration;
+///
+/// fn setup(startup: &mut dyn Startup) {
+///     startup.on_started("announce", || async {
+///         // register with some service
+///         Ok(())
+///     });
+///     startup.add_shutdown_hook(
+///         Hook::new("deregister", || async {
+///             // deregister from some service
+///             Ok(())
+///         })
+///         .timeout(Duration::from_secs(5)),
+///     );
+/// }
+/// ```
+pub struct Hook {
+    name: String,
+    order: i32,
+    timeout: Option<Duration>,
+    hook: Box<dyn FnOnce() -> LocalBoxFuture<'static, anyhow::Result<()>>>,
+}
+
+impl core::fmt::Debug for Hook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Hook")
+            .field("name", &self.name)
+            .field("order", &self.order)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Hook {
+    pub fn new<N, F, Fut>(name: N, hook: F) -> Self
+    where
+        N: Into<String>,
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        Self {
+            name: name.into(),
+            order: 0,
+            timeout: None,
+            hook: Box::new(move || hook().boxed_local()),
+        }
+    }
+
+    /// Set the order of the hook, hooks with a lower order are executed first. Defaults to `0`.
+    pub fn order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Set the maximum time the hook may take.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn run(self) -> anyhow::Result<()> {
+        let name = self.name;
+        let future = (self.hook)();
+        match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, future).await {
+                Ok(result) => result,
+                Err(_) => {
+                    anyhow::bail!("Hook '{name}' timed out after {}", format_duration(timeout))
+                }
+            },
+            None => future.await,
+        }
+    }
+}
+
+/// Run the hooks when the application started, stopping at the first failure.
+pub(crate) async fn run_started(mut hooks: Vec<Hook>) -> anyhow::Result<()> {
+    hooks.sort_by_key(|hook| hook.order);
+
+    for hook in hooks {
+        let name = hook.name.clone();
+        log::debug!("Running started hook '{name}'");
+        hook.run()
+            .await
+            .map_err(|err| err.context(format!("Started hook '{name}' failed")))?;
+    }
+
+    Ok(())
+}
+
+/// Run the hooks when the application shuts down, logging failures.
+pub(crate) async fn run_shutdown(mut hooks: Vec<Hook>) {
+    hooks.reverse();
+    hooks.sort_by_key(|hook| hook.order);
+
+    for hook in hooks {
+        let name = hook.name.clone();
+        log::debug!("Running shutdown hook '{name}'");
+        if let Err(err) = hook.run().await {
+            log::warn!("Shutdown hook '{name}' failed: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn recording(events: &Rc<RefCell<Vec<&'static str>>>, name: &'static str, order: i32) -> Hook {
+        let events = events.clone();
+        Hook::new(name, move || async move {
+            events.borrow_mut().push(name);
+            Ok(())
+        })
+        .order(order)
+    }
+
+    #[tokio::test]
+    async fn test_order() {
+        let events = Rc::new(RefCell::new(vec![]));
+
+        run_started(vec![
+            recording(&events, "a", 0),
+            recording(&events, "b", -1),
+            recording(&events, "c", 0),
+        ])
+        .await
+        .unwrap();
+
+        run_shutdown(vec![
+            recording(&events, "d", 0),
+            recording(&events, "e", 1),
+            recording(&events, "f", 0),
+        ])
+        .await;
+
+        assert_eq!(*events.borrow(), vec!["b", "a", "c", "f", "d", "e"]);
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let result = run_started(vec![Hook::new("slow", || async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        })
+        .timeout(Duration::from_millis(1))])
+        .await;
+
+        assert!(result.is_err());
+    }
+}
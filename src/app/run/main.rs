@@ -1,28 +1,48 @@
+use super::hooks::{self, Hook};
 use crate::{
-    app::{health::HealthChecker, RuntimeConfig, Startup},
-    core::{config::ConfigFromEnv, Spawner},
+    app::{
+        health::{CheckHandle, HealthChecker, HealthServer},
+        RuntimeConfig, Startup,
+    },
+    core::{
+        config::{ConfigFromEnv, ConfigSources},
+        info::ComponentInformation,
+        task::{ShutdownToken, ShutdownTrigger, Task, TaskMonitor, TaskPolicy},
+        Spawner,
+    },
     health::HealthChecked,
 };
+use chrono::{DateTime, Utc};
 use futures_core::future::LocalBoxFuture;
-use futures_util::future::FutureExt;
+use futures_util::stream::{FuturesUnordered, StreamExt};
 use humantime::format_duration;
 use prometheus::{Encoder, TextEncoder};
+use std::cell::Cell;
 use std::future::Future;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
-
-#[cfg(feature = "actix")]
-use crate::app::health::HealthServer;
+use std::rc::Rc;
+use std::time::Duration;
+use tokio::time::Instant;
 
 /// A main runner.
 ///
 /// The idea of the main runner is to perform all setup steps, gathering all tasks (futures) to be
 /// executed, and then initialize the stack and drive the tasks, until one of them completes.
 ///
+/// The readiness check fails until all tasks are running and all started hooks completed.
+///
+/// When receiving `SIGTERM` or `SIGINT`, the main runner will perform a graceful shutdown: the
+/// readiness check starts failing, and after the drain period the [`ShutdownToken`] gets
+/// triggered. Once all tasks completed, or the shutdown timeout expired, the runner returns.
+///
 /// In some cases it might be necessary to run a set of tasks on a different context (like actix, or
 /// ntex). In this case it is possible to create a [`SubMain`] instance using [`SubMain::sub_main`].
 pub struct Main<'m> {
     sub: SubMain<'m>,
+    component: Option<ComponentInformation>,
+    started: DateTime<Utc>,
+    shutdown: ShutdownTrigger,
 }
 
 impl<'m> Default for Main<'m> {
@@ -47,8 +67,16 @@ impl<'m> DerefMut for Main<'m> {
 
 impl<'m> Main<'m> {
     pub fn new(config: RuntimeConfig) -> Self {
+        let shutdown = ShutdownTrigger::new();
+        let mut health = HealthChecker::new(config.health.check_timeout).starting();
+        if config.health.refresh_interval.is_some() {
+            health = health.cached(config.health.max_age);
+        }
         Self {
-            sub: SubMain::new(config, Default::default()),
+            sub: SubMain::new(config, health, shutdown.clone()),
+            component: None,
+            started: Utc::now(),
+            shutdown,
         }
     }
 
@@ -56,6 +84,29 @@ impl<'m> Main<'m> {
         Ok(Self::new(RuntimeConfig::from_env_prefix("RUNTIME")?))
     }
 
+    /// Set the configuration sources, which the runtime configuration was loaded from.
+    pub fn config_sources(mut self, sources: ConfigSources) -> Self {
+        self.sub.sources = sources;
+        self
+    }
+
+    /// Set the component information, which will be provided by the health server.
+    pub fn component(mut self, component: ComponentInformation) -> Self {
+        self.component = Some(component);
+        self
+    }
+
+    /// Set the task monitor, tracking the tasks of this and all sub-main instances.
+    pub fn task_monitor(mut self, monitor: TaskMonitor) -> Self {
+        self.sub.monitor = monitor;
+        self
+    }
+
+    /// The time this instance was created.
+    pub fn started(&self) -> DateTime<Utc> {
+        self.started
+    }
+
     /// Add tasks to run.
     pub fn add_tasks<I>(mut self, tasks: I) -> Self
     where
@@ -72,40 +123,94 @@ impl<'m> Main<'m> {
         self.sub.health.extend(i);
     }
 
-    pub async fn run(mut self) -> anyhow::Result<()> {
+    pub async fn run(self) -> anyhow::Result<()> {
+        self.run_until(shutdown_signal()).await
+    }
+
+    /// Run until the signal requests the shutdown of the application.
+    async fn run_until<S>(mut self, signal: S) -> anyhow::Result<()>
+    where
+        S: Future<Output = &'static str>,
+    {
         log::info!("Starting main ...");
         log::debug!("Runtime configuration: {:#?}", self.config);
 
         self.run_console_metrics();
         self.run_health_server();
+        self.run_health_refresh();
+
+        // mark as started, after all other started hooks
+        let health = self.health.clone();
+        self.add_started_hook(
+            Hook::new("health-started", move || async move {
+                health.started();
+                Ok(())
+            })
+            .order(i32::MAX),
+        );
+
+        let config = self.config.shutdown.clone();
+        let health = self.health.clone();
+        let shutdown = self.shutdown;
+        let deadline = self.sub.deadline.clone();
+
+        // the sub-main enforces the shutdown timeout, and runs the shutdown hooks afterwards, so
+        // it must not get cancelled
+        let run = self.sub.run();
+        futures_util::pin_mut!(run);
+
+        tokio::select! {
+            result = &mut run => return result,
+            signal = signal => {
+                log::info!("Received {signal}, shutting down...");
+            }
+        }
 
-        self.sub.run().await
+        // the timeout covers the whole shutdown, including the drain period
+        deadline.set(Some(Instant::now() + config.timeout));
+
+        health.shutting_down();
+        if !config.drain_period.is_zero() {
+            log::info!("Draining for {}", format_duration(config.drain_period));
+            // keep running the tasks while draining
+            tokio::select! {
+                result = &mut run => return result,
+                _ = tokio::time::sleep(config.drain_period.min(config.timeout)) => {}
+            }
+        }
+        shutdown.trigger();
+
+        run.await
+    }
+
+    fn run_health_refresh(&mut self) {
+        if let Some(interval) = self.config.health.refresh_interval {
+            let health = self.health.clone();
+            let shutdown = self.shutdown.token();
+            self.spawn_task(
+                Task::new("health-refresh", health.run_refresh(interval, shutdown)).graceful(),
+            );
+        }
     }
 
-    #[cfg(feature = "actix")]
     fn run_health_server(&mut self) {
         log::info!("Health server: {}", self.config.health.enabled);
 
         if self.config.health.enabled {
-            let health = HealthServer::new(
+            let mut health = HealthServer::new(
                 self.config.health.clone(),
                 self.health.clone(),
                 Some(prometheus::default_registry().clone()),
-            );
-
-            self.tasks.push(health.run().boxed());
-        }
-    }
-
-    #[cfg(not(feature = "actix"))]
-    fn run_health_server(&self) {
-        log::info!(
-            "No health server implementation (required?: {})",
-            self.config.health.enabled
-        );
-
-        if self.config.health.enabled {
-            panic!("Unable to run health endpoint without 'actix' feature. Either enable 'actix' during compilation or disable the health server during runtime.");
+            )
+            .runtime_config(self.config.clone())
+            .started(self.started)
+            .task_monitor(self.monitor.clone())
+            .shutdown_token(self.shutdown.token());
+            if let Some(component) = self.component {
+                health = health.component(component);
+            }
+
+            self.spawn_task(Task::new("health-server", health.run()).graceful());
         }
     }
 
@@ -113,8 +218,9 @@ impl<'m> Main<'m> {
         if self.config.console_metrics.enabled {
             let period = self.config.console_metrics.period;
 
-            self.tasks.push(
-                async move {
+            let shutdown = self.shutdown.token();
+            self.spawn_task(
+                Task::new("console-metrics", async move {
                     log::info!(
                         "Starting console metrics loop ({})...",
                         format_duration(period)
@@ -126,10 +232,13 @@ impl<'m> Main<'m> {
                             let mut out = std::io::stdout().lock();
                             encoder.encode(&metric_families, &mut out).unwrap();
                         }
-                        tokio::time::sleep(period).await;
+                        tokio::select! {
+                            _ = tokio::time::sleep(period) => {}
+                            _ = shutdown.triggered() => return Ok(()),
+                        }
                     }
-                }
-                .boxed(),
+                })
+                .graceful(),
             );
         }
     }
@@ -139,6 +248,10 @@ impl Spawner for Main<'_> {
     fn spawn_boxed(&mut self, future: Pin<Box<dyn Future<Output = anyhow::Result<()>>>>) {
         SubMain::spawn_boxed(self, future)
     }
+
+    fn spawn_task(&mut self, task: Task) {
+        SubMain::spawn_task(self, task)
+    }
 }
 
 impl Startup for Main<'_> {
@@ -146,6 +259,10 @@ impl Startup for Main<'_> {
         SubMain::check_boxed(self, check)
     }
 
+    fn add_check_boxed(&mut self, check: Box<dyn HealthChecked>) -> CheckHandle {
+        SubMain::add_check_boxed(self, check)
+    }
+
     fn use_tracing(&self) -> bool {
         SubMain::use_tracing(self)
     }
@@ -153,6 +270,66 @@ impl Startup for Main<'_> {
     fn runtime_config(&self) -> &RuntimeConfig {
         SubMain::runtime_config(self)
     }
+
+    fn shutdown_token(&self) -> ShutdownToken {
+        SubMain::shutdown_token(self)
+    }
+
+    fn config_sources(&self) -> Option<&ConfigSources> {
+        SubMain::config_sources(self)
+    }
+
+    fn add_started_hook(&mut self, hook: Hook) {
+        SubMain::add_started_hook(self, hook)
+    }
+
+    fn add_shutdown_hook(&mut self, hook: Hook) {
+        SubMain::add_shutdown_hook(self, hook)
+    }
+}
+
+/// Wait for a signal requesting the shutdown of the application.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::interrupt()),
+        ) {
+            (Ok(mut term), Ok(mut int)) => tokio::select! {
+                _ = term.recv() => "SIGTERM",
+                _ = int.recv() => "SIGINT",
+            },
+            (Err(err), _) | (_, Err(err)) => {
+                log::warn!("Failed to install signal handlers: {err}");
+                futures_util::future::pending().await
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "Ctrl-C",
+            Err(err) => {
+                log::warn!("Failed to install signal handler: {err}");
+                futures_util::future::pending().await
+            }
+        }
+    }
+}
+
+/// The error returned when running a main instance, in case a task failed.
+#[derive(Debug, thiserror::Error)]
+#[error("Task '{name}' failed")]
+pub struct TaskFailed {
+    /// The name of the task.
+    pub name: String,
+    /// The cause of the failure.
+    #[source]
+    pub source: anyhow::Error,
 }
 
 /// A sub-main instance, which can be used to contribute global tasks to the main instance which
@@ -160,19 +337,56 @@ impl Startup for Main<'_> {
 /// the [`SubMain::run`] function.
 pub struct SubMain<'m> {
     config: RuntimeConfig,
-    tasks: Vec<LocalBoxFuture<'m, anyhow::Result<()>>>,
+    sources: ConfigSources,
+    tasks: Vec<SubTask<'m>>,
     health: HealthChecker,
+    shutdown: ShutdownTrigger,
+    started_hooks: Vec<Hook>,
+    shutdown_hooks: Vec<Hook>,
+    monitor: TaskMonitor,
+    /// The deadline of the graceful shutdown, if it was already set by the main instance.
+    deadline: Rc<Cell<Option<Instant>>>,
 }
 
-impl SubMain<'_> {
-    pub(crate) fn new(config: RuntimeConfig, health: HealthChecker) -> Self {
+struct SubTask<'m> {
+    name: String,
+    /// If the task must complete during the shutdown.
+    graceful: bool,
+    future: LocalBoxFuture<'m, anyhow::Result<()>>,
+}
+
+impl<'m> SubMain<'m> {
+    pub(crate) fn new(
+        config: RuntimeConfig,
+        health: HealthChecker,
+        shutdown: ShutdownTrigger,
+    ) -> Self {
         Self {
             config,
+            sources: Default::default(),
             tasks: Default::default(),
             health,
+            shutdown,
+            started_hooks: Default::default(),
+            shutdown_hooks: Default::default(),
+            monitor: Default::default(),
+            deadline: Default::default(),
         }
     }
 
+    fn push_unnamed(&mut self, future: LocalBoxFuture<'m, anyhow::Result<()>>) {
+        self.tasks.push(SubTask {
+            name: self.monitor.unnamed(),
+            graceful: false,
+            future,
+        });
+    }
+
+    /// The monitor, tracking the status of the tasks.
+    pub fn task_monitor(&self) -> &TaskMonitor {
+        &self.monitor
+    }
+
     /// Returns `true` is there are no tasks scheduled so far.
     pub fn is_empty(&self) -> bool {
         self.tasks.is_empty()
@@ -185,33 +399,195 @@ impl SubMain<'_> {
 
     /// Create a seed or a sub-main instance, which can be sent.
     pub fn sub_main_seed(&self) -> SubMainSeed {
-        SubMainSeed::new(self.config.clone(), self.health.clone())
+        SubMainSeed::new(
+            self.config.clone(),
+            self.sources.clone(),
+            self.health.clone(),
+            self.shutdown.clone(),
+            self.monitor.clone(),
+        )
+    }
+
+    /// Spawn a task, running a new sub-main instance on a dedicated thread.
+    ///
+    /// The `setup` function will be called on the new thread, and can be used to register tasks,
+    /// which don't need to be [`Send`]. The tasks will then be driven by a single threaded tokio
+    /// runtime. The sub-main instance shares the health checks and shutdown of this instance,
+    /// and its result will be the result of the spawned task.
+    pub fn spawn_thread<N, F>(&mut self, name: N, setup: F)
+    where
+        N: Into<String>,
+        F: FnOnce(&mut SubMain<'static>) -> anyhow::Result<()> + Send + 'static,
+    {
+        let name = name.into();
+        let seed = self.sub_main_seed();
+        self.spawn_task(Task::new(name.clone(), seed.run_thread(name, setup)).graceful());
     }
 
     /// Run the recorded tasks.
     ///
+    /// This runs until the first task completes, or the shutdown gets triggered. A completing
+    /// task triggers the shutdown itself. During the shutdown, this waits until all graceful
+    /// tasks completed (see [`Task::graceful`]), at most for the configured shutdown timeout.
+    /// All other tasks get dropped. The first failure of a task will be returned as a
+    /// [`TaskFailed`] error.
+    ///
+    /// The started hooks are executed alongside the tasks, a failing hook stops the tasks. Once
+    /// the tasks are stopped, the shutdown hooks are executed.
+    ///
     /// **NOTE:** This does not run any health checks, these must be run by the main instance.
     pub async fn run(self) -> anyhow::Result<()> {
         log::info!("Running {} tasks in this main instance", self.tasks.len());
 
-        let (result, _, _) = futures_util::future::select_all(self.tasks).await;
+        let tasks = self
+            .tasks
+            .into_iter()
+            .map(|task| SubTask {
+                future: self.monitor.track(&task.name, task.future),
+                ..task
+            })
+            .collect();
+
+        let timeout = self.config.shutdown.timeout;
+        let result = Self::run_tasks(
+            tasks,
+            self.started_hooks,
+            self.shutdown,
+            timeout,
+            self.deadline,
+        )
+        .await;
+        hooks::run_shutdown(self.shutdown_hooks).await;
+
+        result
+    }
+
+    async fn run_tasks(
+        tasks: Vec<SubTask<'m>>,
+        started_hooks: Vec<Hook>,
+        shutdown: ShutdownTrigger,
+        timeout: Duration,
+        deadline: Rc<Cell<Option<Instant>>>,
+    ) -> anyhow::Result<()> {
+        let mut remaining = tasks.iter().filter(|task| task.graceful).count();
+        let mut tasks = tasks
+            .into_iter()
+            .map(|task| async move { (task.name, task.graceful, task.future.await) })
+            .collect::<FuturesUnordered<_>>();
+
+        let started = hooks::run_started(started_hooks);
+        futures_util::pin_mut!(started);
+        let mut started_done = false;
+
+        let token = shutdown.token();
+        let mut result = Ok(());
+
+        loop {
+            tokio::select! {
+                biased;
+
+                _ = token.triggered() => break,
+                next = &mut started, if !started_done => {
+                    started_done = true;
+                    if let Err(err) = next {
+                        log::error!("{err:#}");
+                        result = Err(err);
+                        break;
+                    }
+                }
+                next = tasks.next() => {
+                    match next {
+                        Some((name, graceful, next)) => {
+                            if graceful {
+                                remaining -= 1;
+                            }
+                            result = completed(name, next);
+                        }
+                        None if !started_done => {
+                            // no tasks, but still wait for the started hooks
+                            if let Err(err) = (&mut started).await {
+                                log::error!("{err:#}");
+                                result = Err(err);
+                            }
+                        }
+                        None => {}
+                    }
+                    break;
+                }
+            }
+        }
+
+        if !token.is_triggered() {
+            log::warn!("Exiting application...");
+            shutdown.trigger();
+        }
+
+        log::info!("Waiting for {remaining} tasks to complete");
+
+        // the main instance may have already started the timeout, when receiving a signal
+        let deadline = deadline.get().unwrap_or_else(|| Instant::now() + timeout);
+
+        let graceful = async {
+            while remaining > 0 {
+                let (name, graceful, next) = match tasks.next().await {
+                    Some(next) => next,
+                    None => break,
+                };
+                if graceful {
+                    remaining -= 1;
+                }
+                if let Err(err) = completed(name, next) {
+                    if result.is_ok() {
+                        result = Err(err);
+                    }
+                }
+            }
+        };
+
+        if tokio::time::timeout_at(deadline, graceful).await.is_err() && result.is_ok() {
+            result = Err(anyhow::anyhow!(
+                "Graceful shutdown timed out after {}",
+                format_duration(timeout)
+            ));
+        }
 
-        log::warn!("One of the main runners returned: {result:?}");
-        log::warn!("Exiting application...");
+        result
+    }
+}
 
-        Ok(())
+/// Log the result of a completed task, turning a failure into a [`TaskFailed`] error.
+fn completed(name: String, result: anyhow::Result<()>) -> anyhow::Result<()> {
+    match result {
+        Ok(()) => {
+            log::info!("Task '{name}' completed");
+            Ok(())
+        }
+        Err(err) => {
+            log::error!("Task '{name}' failed: {err:#}");
+            Err(TaskFailed { name, source: err }.into())
+        }
     }
 }
 
 impl<'m> Extend<LocalBoxFuture<'m, Result<(), anyhow::Error>>> for SubMain<'m> {
     fn extend<T: IntoIterator<Item = LocalBoxFuture<'m, anyhow::Result<()>>>>(&mut self, iter: T) {
-        self.tasks.extend(iter)
+        for task in iter {
+            self.push_unnamed(task);
+        }
     }
 }
 
 impl<'m> Spawner for SubMain<'m> {
     fn spawn_boxed(&mut self, future: Pin<Box<dyn Future<Output = anyhow::Result<()>>>>) {
-        self.tasks.push(future);
+        self.push_unnamed(future);
+    }
+
+    fn spawn_task(&mut self, task: Task) {
+        self.tasks.push(SubTask {
+            name: task.name().to_string(),
+            graceful: task.is_graceful() && task.policy() != &TaskPolicy::Ignore,
+            future: task.into_monitored(self.shutdown.token(), self.monitor.clone()),
+        });
     }
 }
 
@@ -220,6 +596,10 @@ impl<'m> Startup for SubMain<'m> {
         self.health.push(check);
     }
 
+    fn add_check_boxed(&mut self, check: Box<dyn HealthChecked>) -> CheckHandle {
+        self.health.push(check)
+    }
+
     fn use_tracing(&self) -> bool {
         self.config.tracing.is_enabled()
     }
@@ -227,6 +607,22 @@ impl<'m> Startup for SubMain<'m> {
     fn runtime_config(&self) -> &RuntimeConfig {
         &self.config
     }
+
+    fn shutdown_token(&self) -> ShutdownToken {
+        self.shutdown.token()
+    }
+
+    fn config_sources(&self) -> Option<&ConfigSources> {
+        Some(&self.sources)
+    }
+
+    fn add_started_hook(&mut self, hook: Hook) {
+        self.started_hooks.push(hook);
+    }
+
+    fn add_shutdown_hook(&mut self, hook: Hook) {
+        self.shutdown_hooks.push(hook);
+    }
 }
 
 /// A seed for a [`SubMain`] instance.
@@ -236,12 +632,70 @@ impl<'m> Startup for SubMain<'m> {
 /// create a "seed", which can later (after sending) be turned into a proper instance.
 pub struct SubMainSeed {
     config: RuntimeConfig,
+    sources: ConfigSources,
     health: HealthChecker,
+    shutdown: ShutdownTrigger,
+    monitor: TaskMonitor,
+}
+
+impl SubMainSeed {
+    fn new(
+        config: RuntimeConfig,
+        sources: ConfigSources,
+        health: HealthChecker,
+        shutdown: ShutdownTrigger,
+        monitor: TaskMonitor,
+    ) -> Self {
+        Self {
+            config,
+            sources,
+            health,
+            shutdown,
+            monitor,
+        }
+    }
 }
 
 impl SubMainSeed {
-    fn new(config: RuntimeConfig, health: HealthChecker) -> Self {
-        Self { config, health }
+    /// Run a sub-main instance on a new thread, with a single threaded tokio runtime.
+    ///
+    /// The instance runs inside a [`tokio::task::LocalSet`], so that its tasks can spawn
+    /// local tasks, e.g. using [`tokio::task::spawn_local`].
+    ///
+    /// The thread gets started when the returned future is first polled. The future completes
+    /// with the result of the sub-main instance.
+    pub fn run_thread<N, F>(
+        self,
+        name: N,
+        setup: F,
+    ) -> impl Future<Output = anyhow::Result<()>> + Send + 'static
+    where
+        N: Into<String>,
+        F: FnOnce(&mut SubMain<'static>) -> anyhow::Result<()> + Send + 'static,
+    {
+        let name = name.into();
+        async move {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            std::thread::Builder::new()
+                .name(name.clone())
+                .spawn(move || {
+                    let result = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(anyhow::Error::from)
+                        .and_then(|runtime| {
+                            tokio::task::LocalSet::new().block_on(&runtime, async move {
+                                let mut sub: SubMain<'static> = self.into();
+                                setup(&mut sub)?;
+                                sub.run().await
+                            })
+                        });
+                    let _ = tx.send(result);
+                })?;
+
+            rx.await
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("Thread '{name}' terminated unexpectedly")))
+        }
     }
 }
 
@@ -249,8 +703,265 @@ impl From<SubMainSeed> for SubMain<'_> {
     fn from(seed: SubMainSeed) -> Self {
         Self {
             config: seed.config,
+            sources: seed.sources,
             health: seed.health,
+            shutdown: seed.shutdown,
             tasks: Default::default(),
+            started_hooks: Default::default(),
+            shutdown_hooks: Default::default(),
+            monitor: seed.monitor,
+            deadline: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        app::{exit_code, StartupExt},
+        core::task::Task,
+    };
+    use std::process::ExitCode;
+
+    #[tokio::test]
+    async fn test_task_failed() {
+        let mut main = SubMain::new(
+            Default::default(),
+            Default::default(),
+            ShutdownTrigger::new(),
+        );
+        main.spawn_task(Task::new("failing", async { anyhow::bail!("oops") }));
+        main.spawn_task(Task::new(
+            "pending",
+            futures_util::future::pending::<anyhow::Result<()>>(),
+        ));
+
+        let result = main.run().await;
+
+        let err = result.as_ref().unwrap_err().downcast_ref::<TaskFailed>();
+        assert_eq!(err.map(|err| err.name.as_str()), Some("failing"));
+        assert_eq!(exit_code(&result), ExitCode::from(70));
+    }
+
+    #[tokio::test]
+    async fn test_task_completed() {
+        let mut main = SubMain::new(
+            Default::default(),
+            Default::default(),
+            ShutdownTrigger::new(),
+        );
+        main.spawn_task(Task::new("completing", async { Ok(()) }));
+
+        let result = main.run().await;
+
+        assert!(result.is_ok());
+        assert_eq!(exit_code(&result), ExitCode::SUCCESS);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_send() {
+        // both extension traits being in scope must not be ambiguous
+        use crate::core::SpawnerExt;
+
+        let mut main = SubMain::new(
+            Default::default(),
+            Default::default(),
+            ShutdownTrigger::new(),
+        );
+        main.spawn_send(async { anyhow::bail!("oops") });
+
+        let result = main.run().await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_thread() {
+        let mut main = SubMain::new(
+            Default::default(),
+            Default::default(),
+            ShutdownTrigger::new(),
+        );
+
+        main.spawn_thread("thread", |sub| {
+            let local = std::rc::Rc::new(());
+            sub.spawn(async move {
+                let _local = local;
+                anyhow::bail!("oops")
+            });
+            Ok(())
+        });
+
+        let result = main.run().await;
+
+        let err = result.as_ref().unwrap_err().downcast_ref::<TaskFailed>();
+        assert_eq!(err.map(|err| err.name.as_str()), Some("thread"));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_thread_local() {
+        let mut main = SubMain::new(
+            Default::default(),
+            Default::default(),
+            ShutdownTrigger::new(),
+        );
+
+        main.spawn_thread("thread", |sub| {
+            let local = std::rc::Rc::new(());
+            sub.spawn(async move {
+                let local = tokio::task::spawn_local(async move {
+                    let _local = local;
+                });
+                local.await?;
+                Ok(())
+            });
+            Ok(())
+        });
+
+        assert!(main.run().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let trigger = ShutdownTrigger::new();
+        let mut main = SubMain::new(Default::default(), Default::default(), trigger.clone());
+
+        let token = main.shutdown_token();
+        main.spawn_task(
+            Task::new("graceful", async move {
+                token.triggered().await;
+                Ok(())
+            })
+            .graceful(),
+        );
+        let token = main.shutdown_token();
+        main.spawn_task(
+            Task::new("failing", async move {
+                token.triggered().await;
+                anyhow::bail!("oops")
+            })
+            .graceful(),
+        );
+        main.spawn_task(Task::ignored("ignored", async { Ok(()) }));
+        // not watching the shutdown, must not block it
+        main.spawn(futures_util::future::pending());
+
+        trigger.trigger();
+        let result = main.run().await;
+
+        let err = result.as_ref().unwrap_err().downcast_ref::<TaskFailed>();
+        assert_eq!(err.map(|err| err.name.as_str()), Some("failing"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_triggers_shutdown() {
+        let trigger = ShutdownTrigger::new();
+        let mut main = SubMain::new(Default::default(), Default::default(), trigger.clone());
+
+        let stopped = std::rc::Rc::new(std::cell::Cell::new(false));
+        let token = main.shutdown_token();
+        main.spawn_task(
+            Task::new("graceful", {
+                let stopped = stopped.clone();
+                async move {
+                    token.triggered().await;
+                    stopped.set(true);
+                    Ok(())
+                }
+            })
+            .graceful(),
+        );
+        main.spawn_task(Task::new("failing", async { anyhow::bail!("oops") }));
+
+        let result = main.run().await;
+
+        let err = result.as_ref().unwrap_err().downcast_ref::<TaskFailed>();
+        assert_eq!(err.map(|err| err.name.as_str()), Some("failing"));
+        assert!(trigger.token().is_triggered());
+        assert!(stopped.get());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_hooks_after_drain() {
+        let mut config = RuntimeConfig::default();
+        config.shutdown.drain_period = Duration::from_millis(50);
+        config.shutdown.timeout = Duration::from_millis(200);
+        let mut main = Main::new(config);
+
+        let called = Rc::new(Cell::new(false));
+        main.on_shutdown("record", {
+            let called = called.clone();
+            move || async move {
+                called.set(true);
+                Ok(())
+            }
+        });
+        // graceful, but never completes, so the shutdown times out
+        main.spawn_task(
+            Task::new(
+                "stuck",
+                futures_util::future::pending::<anyhow::Result<()>>(),
+            )
+            .graceful(),
+        );
+
+        let result = main.run_until(async { "test" }).await;
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("timed out"), "{err}");
+        assert!(called.get());
+    }
+
+    #[tokio::test]
+    async fn test_started_hooks_without_tasks() {
+        let mut main = SubMain::new(
+            Default::default(),
+            Default::default(),
+            ShutdownTrigger::new(),
+        );
+
+        let called = Rc::new(Cell::new(false));
+        main.on_started("record", {
+            let called = called.clone();
+            move || async move {
+                tokio::task::yield_now().await;
+                called.set(true);
+                Ok(())
+            }
+        });
+        let token = main.shutdown_token();
+
+        assert!(main.run().await.is_ok());
+        assert!(called.get());
+        assert!(token.is_triggered());
+    }
+
+    struct Check;
+
+    #[async_trait::async_trait]
+    impl HealthChecked for Check {
+        async fn is_ready(&self) -> Result<(), crate::health::HealthCheckError> {
+            Ok(())
         }
     }
+
+    #[test]
+    fn test_check_registered() {
+        let mut main = Main::default();
+        let handle = main.add_check(Check);
+        assert_eq!(main.health.names().len(), 1);
+
+        assert!(handle.remove());
+        assert!(main.health.names().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_ready() {
+        let mut main = Main::default();
+        main.check(Check);
+
+        let report = main.health.ready_report().await;
+        assert!(report.checks.iter().any(|c| c.name == Check.name()));
+    }
 }
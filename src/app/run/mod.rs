@@ -1,16 +1,30 @@
+mod hooks;
 mod main;
 
+pub use hooks::Hook;
 pub use main::*;
 
-use crate::app::init::{self, Tracing};
-use crate::core::{config::ConfigFromEnv, info::ComponentInformation};
-use crate::{app::health::HealthServerConfig, core::Spawner, health::HealthChecked};
+use crate::app::init::{self, LogConfig, LogFilterHandle, Tracing};
+use crate::core::{
+    config::{
+        validate, ConfigFromEnv, ConfigReference, ConfigReloader, ConfigSources, Reference,
+        ValidationReport,
+    },
+    info::ComponentInformation,
+    task::{ShutdownToken, Task, TaskMonitor},
+};
+use crate::{
+    app::health::{CheckHandle, HealthServerConfig},
+    core::Spawner,
+    health::HealthChecked,
+};
 use std::future::Future;
 use std::io::Write;
 use std::pin::Pin;
+use std::process::ExitCode;
 use std::time::Duration;
 
-#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct RuntimeConfig {
     #[serde(default)]
     pub console_metrics: ConsoleMetrics,
@@ -18,9 +32,33 @@ pub struct RuntimeConfig {
     pub health: HealthServerConfig,
     #[serde(default)]
     pub tracing: Tracing,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
 }
 
-#[derive(Clone, Debug, serde::Deserialize)]
+/// Configuration of the graceful shutdown, when receiving `SIGTERM` or `SIGINT`.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ShutdownConfig {
+    /// The time to wait after failing the readiness check, before stopping the tasks.
+    #[serde(default, with = "humantime_serde")]
+    pub drain_period: Duration,
+    /// The maximum time of the whole shutdown, including the drain period.
+    #[serde(default = "default::shutdown_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_period: Duration::ZERO,
+            timeout: default::shutdown_timeout(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct ConsoleMetrics {
     pub enabled: bool,
     #[serde(
@@ -45,12 +83,66 @@ mod default {
     pub const fn console_metrics_duration() -> Duration {
         Duration::from_secs(60)
     }
+
+    pub const fn shutdown_timeout() -> Duration {
+        Duration::from_secs(30)
+    }
+}
+
+impl ConfigReference for RuntimeConfig {
+    fn describe(r: &mut Reference) {
+        r.nested_optional::<ConsoleMetrics>("console_metrics");
+        r.nested_optional::<HealthServerConfig>("health");
+        r.with_default(
+            "tracing",
+            "`disabled` | `jaeger`",
+            "disabled",
+            "The tracing implementation to use",
+        );
+        r.nested_optional::<LogConfig>("log");
+        r.nested_optional::<ShutdownConfig>("shutdown");
+    }
+}
+
+impl ConfigReference for ShutdownConfig {
+    fn describe(r: &mut Reference) {
+        r.with_default(
+            "drain_period",
+            "duration",
+            "0s",
+            "The time to wait after failing the readiness check, before stopping the tasks",
+        );
+        r.with_default(
+            "timeout",
+            "duration",
+            humantime::format_duration(default::shutdown_timeout()),
+            "The maximum time of the whole shutdown, including the drain period",
+        );
+    }
+}
+
+impl ConfigReference for ConsoleMetrics {
+    fn describe(r: &mut Reference) {
+        r.required(
+            "enabled",
+            "boolean",
+            "Periodically print metrics to the console",
+        );
+        r.with_default(
+            "period",
+            "duration",
+            humantime::format_duration(default::console_metrics_duration()),
+            "The period in which metrics will be printed",
+        );
+    }
 }
 
 pub struct Runtime {
     component: ComponentInformation,
     dotenv: Option<bool>,
     show_banner: Option<bool>,
+    sources: ConfigSources,
+    reference: Option<fn(&mut Reference)>,
 }
 
 /// Create a new runtime, using the local crate as component.
@@ -85,6 +177,8 @@ impl Runtime {
             component,
             dotenv: None,
             show_banner: None,
+            sources: Default::default(),
+            reference: None,
         }
     }
 
@@ -106,6 +200,72 @@ impl Runtime {
         self
     }
 
+    /// Set the configuration sources.
+    ///
+    /// Both the [`RuntimeConfig`] (from the section `runtime`) and the application configuration
+    /// will be loaded from the same sources. By default, only environment variables are used.
+    ///
+    /// ```
+    /// use drogue_bazaar::{project, runtime, core::config::ConfigSources};
+    ///
+    /// project!(PROJECT: "Drogue IoT");
+    ///
+    /// fn main() {
+    ///     runtime!(PROJECT)
+    ///         .config_sources(
+    ///             ConfigSources::new()
+    ///                 .file("/etc/config/default.yaml")
+    ///                 .optional_file("/etc/config/override.yaml")
+    ///         );
+    /// }
+    /// ```
+    #[allow(clippy::needless_doctest_main)]
+    pub fn config_sources(mut self, sources: ConfigSources) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Register the configuration reference of the application configuration.
+    ///
+    /// Running the application with the argument `--print-config-reference` will print a
+    /// reference of all environment variables, including the ones of the runtime, and exit. The
+    /// format can be selected using `--print-config-reference=json` or
+    /// `--print-config-reference=markdown` (the default).
+    pub fn config_reference<R: ConfigReference>(mut self) -> Self {
+        self.reference = Some(R::describe);
+        self
+    }
+
+    /// Print the configuration reference, if requested by the command line arguments.
+    ///
+    /// Returns `true` if the reference was printed.
+    fn print_reference(&self) -> anyhow::Result<bool> {
+        let format = std::env::args().skip(1).find_map(|arg| {
+            arg.strip_prefix("--print-config-reference")
+                .filter(|rest| rest.is_empty() || rest.starts_with('='))
+                .map(|rest| rest.trim_start_matches('=').to_string())
+        });
+
+        let format = match format {
+            Some(format) => format,
+            None => return Ok(false),
+        };
+
+        let mut reference =
+            Reference::new(self.sources.env_prefix()).section::<RuntimeConfig>("runtime");
+        if let Some(describe) = self.reference {
+            describe(&mut reference);
+        }
+
+        match format.as_str() {
+            "" | "markdown" => println!("{}", reference.to_markdown()),
+            "json" => println!("{}", reference.to_json()?),
+            format => anyhow::bail!("Unknown configuration reference format: {format}"),
+        }
+
+        Ok(true)
+    }
+
     /// Show the application banner
     fn banner(&self) {
         if self
@@ -125,15 +285,58 @@ impl Runtime {
                 self.component.description
             );
 
+            if !self.component.build.is_empty() {
+                println!("Build: {}", self.component.build);
+                println!();
+            }
+
             std::io::stdout().flush().ok();
         }
     }
 
+    /// Load the runtime and the application configuration.
+    ///
+    /// In case of an error, the report will contain all issues of both configurations.
+    fn load_config<C>(&self) -> Result<(RuntimeConfig, C), ValidationReport>
+    where
+        for<'de> C: ConfigFromEnv<'de>,
+    {
+        let config = self
+            .sources
+            .build()
+            .map_err(|err| ValidationReport::from_error(&err))?;
+        let prefix = self.sources.env_prefix();
+
+        match (
+            validate::<RuntimeConfig>(&config, Some("runtime"), prefix),
+            validate::<C>(&config, None, prefix),
+        ) {
+            (Ok(runtime), Ok(config)) => Ok((runtime, config)),
+            (runtime, config) => {
+                let mut report = ValidationReport::default();
+                report.extend(runtime.err().into_iter().flat_map(|r| r.issues));
+                report.extend(config.err().into_iter().flat_map(|r| r.issues));
+                Err(report)
+            }
+        }
+    }
+
+    /// Run the application.
+    ///
+    /// If the configuration is invalid, this will fail with a [`ValidationReport`], listing all
+    /// issues, and the environment variables they originate from. If a task fails, this will fail
+    /// with a [`TaskFailed`] error. Use [`exit_code`] to map the result to an exit code.
     pub async fn exec<C, A>(self, app: A) -> anyhow::Result<()>
     where
         A: App<C>,
         for<'de> C: ConfigFromEnv<'de>,
     {
+        // phase 0: print the configuration reference, if requested
+
+        if self.print_reference()? {
+            return Ok(());
+        }
+
         // phase 1: early init, cannot really rely on env-vars, but may add its own
 
         init::phase1(
@@ -146,21 +349,79 @@ impl Runtime {
 
         // phase 3: env-vars are ready now, we can make use of them
 
-        let mut main = Main::from_env()?;
-        init::phase2(self.component.name, main.runtime_config().tracing.clone());
+        let (runtime, config) = self.load_config::<C>()?;
+
+        if let Err(err) = self
+            .component
+            .register_build_info(prometheus::default_registry())
+        {
+            log::warn!("Failed to register build information metric: {err}");
+        }
+
+        let mut main = Main::new(runtime)
+            .config_sources(self.sources.clone())
+            .component(self.component);
+
+        match TaskMonitor::with_registry(prometheus::default_registry()) {
+            Ok(monitor) => main = main.task_monitor(monitor),
+            Err(err) => log::warn!("Failed to register task metrics: {err}"),
+        }
+        let log_filter = init::phase2_with_log(
+            self.component.name,
+            main.runtime_config().tracing.clone(),
+            &main.runtime_config().log,
+        );
+        if let Some(log_filter) = log_filter {
+            self.reload_log_filter(&mut main, log_filter);
+        }
 
         // phase 4: main app startup
 
-        let config = C::from_env()?;
-        app.run(config, &mut main).await?;
-        main.run().await?;
+        let timeout = main.runtime_config().shutdown.timeout;
+        let result = match app.run(config, &mut main).await {
+            Ok(()) => main.run().await,
+            Err(err) => Err(err),
+        };
 
-        // exiting, shutdown tracing (flush)
-        opentelemetry::global::shutdown_tracer_provider();
+        // exiting, shutdown tracing (flush), which might block
+        let flush = tokio::task::spawn_blocking(opentelemetry::global::shutdown_tracer_provider);
+        if tokio::time::timeout(timeout, flush).await.is_err() {
+            log::warn!("Timed out flushing traces");
+        }
 
         // done
 
-        Ok(())
+        result
+    }
+
+    /// Apply changes of the log filter, in case the configuration was loaded from files.
+    fn reload_log_filter(&self, main: &mut Main, handle: LogFilterHandle) {
+        if self.sources.paths().next().is_none() {
+            return;
+        }
+
+        let reloader =
+            match ConfigReloader::<LogConfig>::new(self.sources.clone(), Some("runtime.log")) {
+                Ok(reloader) => reloader,
+                Err(err) => {
+                    log::warn!("Unable to reload the log configuration: {err}");
+                    return;
+                }
+            };
+
+        let mut config = reloader.subscribe();
+        main.spawn_task(Task::ignored("log-config-reload", async move {
+            let apply = async move {
+                while let Some(config) = config.changed().await {
+                    log::info!("Applying log filter: {:?}", config.filter);
+                    handle.set_filter(config.filter.as_deref());
+                }
+                Ok(())
+            };
+            futures_util::future::try_join(reloader.run(), apply)
+                .await
+                .map(|_| ())
+        }));
     }
 
     pub async fn exec_fn<C, F>(self, f: F) -> anyhow::Result<()>
@@ -203,6 +464,45 @@ where
     }
 }
 
+/// Map the result of running an application into a process exit code.
+///
+/// | Result                | Exit code            |
+/// | --------------------- | -------------------- |
+/// | Success               | `0`                  |
+/// | Invalid configuration | `78` (`EX_CONFIG`)   |
+/// | Failed task           | `70` (`EX_SOFTWARE`) |
+/// | Any other error       | `1`                  |
+///
+/// ```
+/// use drogue_bazaar::{project, runtime, app::{exit_code, Startup}};
+/// use std::process::ExitCode;
+///
+/// project!(PROJECT: "Drogue IoT");
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {}
+///
+/// async fn run(config: Config, startup: &mut dyn Startup) -> anyhow::Result<()> {
+///     Ok(())
+/// }
+///
+/// async fn run_main() -> ExitCode {
+///     let result = runtime!(PROJECT).exec(run).await;
+///     if let Err(err) = &result {
+///         eprintln!("{err:#}");
+///     }
+///     exit_code(&result)
+/// }
+/// ```
+pub fn exit_code(result: &anyhow::Result<()>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) if err.is::<ValidationReport>() => ExitCode::from(78),
+        Err(err) if err.is::<TaskFailed>() => ExitCode::from(70),
+        Err(_) => ExitCode::FAILURE,
+    }
+}
+
 fn flag(name: &str) -> bool {
     flag_opt(name).unwrap_or_default()
 }
@@ -216,6 +516,16 @@ pub trait Startup: Spawner {
     /// Add a health check.
     fn check_boxed(&mut self, check: Box<dyn HealthChecked>);
 
+    /// Add a health check, returning a handle to it.
+    ///
+    /// The check is registered immediately, and can be removed again using the returned handle.
+    /// The default implementation registers the check using [`Self::check_boxed`], returning
+    /// a handle which can't remove it.
+    fn add_check_boxed(&mut self, check: Box<dyn HealthChecked>) -> CheckHandle {
+        self.check_boxed(check);
+        CheckHandle::detached()
+    }
+
     /// Allow the application to check if the runtime wants to enable tracing.
     ///
     /// This can be used to e.g. add some tracing logger into the HTTP stack.
@@ -223,6 +533,33 @@ pub trait Startup: Spawner {
 
     /// Access the runtime config.
     fn runtime_config(&self) -> &RuntimeConfig;
+
+    /// Get a token, which gets triggered when the application is shutting down.
+    ///
+    /// Tasks should complete when the shutdown was triggered, so that the application can exit
+    /// gracefully. The default implementation returns a token which is never triggered.
+    fn shutdown_token(&self) -> ShutdownToken {
+        ShutdownToken::never()
+    }
+
+    /// Access the sources the configuration was loaded from, if they are known.
+    ///
+    /// This can be used to create a [`crate::core::config::ConfigReloader`] for parts of the
+    /// configuration which can be changed at runtime. The default implementation returns
+    /// [`None`].
+    fn config_sources(&self) -> Option<&ConfigSources> {
+        None
+    }
+
+    /// Add a hook, which gets executed once the application started.
+    ///
+    /// If a started hook fails, the application will exit.
+    fn add_started_hook(&mut self, hook: Hook);
+
+    /// Add a hook, which gets executed once all tasks completed, when the application exits.
+    ///
+    /// Failing shutdown hooks will only be logged.
+    fn add_shutdown_hook(&mut self, hook: Hook);
 }
 
 pub trait StartupExt: Startup {
@@ -243,6 +580,16 @@ pub trait StartupExt: Startup {
         self.check_boxed(Box::new(c))
     }
 
+    /// Add a health check, returning a handle to it.
+    ///
+    /// Also see [`Startup::add_check_boxed`].
+    fn add_check<C>(&mut self, c: C) -> CheckHandle
+    where
+        C: HealthChecked + 'static,
+    {
+        self.add_check_boxed(Box::new(c))
+    }
+
     fn spawn_iter<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = Pin<Box<dyn Future<Output = anyhow::Result<()>>>>>,
@@ -258,6 +605,47 @@ pub trait StartupExt: Startup {
     {
         self.spawn_boxed(Box::pin(f))
     }
+
+    /// Run a hook once the application started.
+    fn on_started<N, F, Fut>(&mut self, name: N, f: F)
+    where
+        N: Into<String>,
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        self.add_started_hook(Hook::new(name, f))
+    }
+
+    /// Run a hook when the application exits.
+    fn on_shutdown<N, F, Fut>(&mut self, name: N, f: F)
+    where
+        N: Into<String>,
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        self.add_shutdown_hook(Hook::new(name, f))
+    }
 }
 
 impl<S: ?Sized> StartupExt for S where S: Startup {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::assert_reference;
+
+    #[test]
+    fn test_reference() {
+        assert_reference::<RuntimeConfig>();
+    }
+
+    #[test]
+    fn test_reference_shutdown() {
+        assert_reference::<ShutdownConfig>();
+    }
+
+    #[test]
+    fn test_reference_console_metrics() {
+        assert_reference::<ConsoleMetrics>();
+    }
+}
@@ -1,12 +1,17 @@
-use crate::{auth::openid::ExtendedClaims, core::config::CommaSeparatedVec};
+use crate::{
+    auth::openid::ExtendedClaims,
+    core::config::{ConfigReference, Delimited, Reference, Reloadable, Secret},
+};
 use anyhow::Context;
 use core::fmt::{Debug, Formatter};
-use futures_util::{stream, StreamExt, TryStreamExt};
+use futures_util::{stream, Future, StreamExt, TryStreamExt};
 use openid::{
     biscuit::jws::Compact, Claims, Client, CompactJson, Configurable, Discovered, Empty, Jws,
 };
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 use thiserror::Error;
 use tracing::instrument;
 use url::Url;
@@ -34,13 +39,13 @@ pub struct AuthenticatorGlobalConfig {
     pub tls_insecure: bool,
 
     #[serde(default)]
-    pub tls_ca_certificates: CommaSeparatedVec,
+    pub tls_ca_certificates: Delimited<PathBuf>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
 pub struct AuthenticatorClientConfig {
     pub client_id: String,
-    pub client_secret: String,
+    pub client_secret: Secret<String>,
     #[serde(default = "defaults::oauth2_scopes")]
     pub scopes: String,
     #[serde(default)]
@@ -49,7 +54,7 @@ pub struct AuthenticatorClientConfig {
     #[serde(default)]
     pub tls_insecure: Option<bool>,
     #[serde(default)]
-    pub tls_ca_certificates: Option<CommaSeparatedVec>,
+    pub tls_ca_certificates: Option<Delimited<PathBuf>>,
 }
 
 mod defaults {
@@ -59,6 +64,64 @@ mod defaults {
     }
 }
 
+impl ConfigReference for AuthenticatorConfig {
+    fn describe(r: &mut Reference) {
+        r.with_default("disabled", "boolean", false, "Disable authentication");
+        r.flatten::<AuthenticatorGlobalConfig>();
+        r.map::<AuthenticatorClientConfig>("clients", "name");
+    }
+}
+
+impl ConfigReference for AuthenticatorGlobalConfig {
+    fn describe(r: &mut Reference) {
+        r.optional(
+            "issuer_url",
+            "url",
+            "The issuer URL, used by clients which don't provide their own",
+        );
+        r.optional("redirect_url", "url", "The redirect URL");
+        r.with_default(
+            "tls_insecure",
+            "boolean",
+            false,
+            "Disable TLS validation, do not use this in production",
+        );
+        r.optional(
+            "tls_ca_certificates",
+            "comma separated list",
+            "Additional CA certificates (PEM files)",
+        );
+    }
+}
+
+impl ConfigReference for AuthenticatorClientConfig {
+    fn describe(r: &mut Reference) {
+        r.required("client_id", "string", "The OAuth2 client ID");
+        r.required("client_secret", "secret", "The OAuth2 client secret");
+        r.with_default(
+            "scopes",
+            "string",
+            defaults::oauth2_scopes(),
+            "The OAuth2 scopes to request",
+        );
+        r.optional(
+            "issuer_url",
+            "url",
+            "The issuer URL, overriding the global one",
+        );
+        r.optional(
+            "tls_insecure",
+            "boolean",
+            "Disable TLS validation, overriding the global setting",
+        );
+        r.optional(
+            "tls_ca_certificates",
+            "comma separated list",
+            "Additional CA certificates, overriding the global setting",
+        );
+    }
+}
+
 impl AuthenticatorConfig {
     /// Create a client from a configuration. This respects the "disabled" field and returns
     /// `None` in this case.
@@ -77,7 +140,7 @@ impl ClientConfig for (&AuthenticatorGlobalConfig, &AuthenticatorClientConfig) {
     }
 
     fn client_secret(&self) -> String {
-        self.1.client_secret.clone()
+        self.1.client_secret.expose().clone()
     }
 
     fn redirect_url(&self) -> Option<String> {
@@ -103,9 +166,11 @@ impl ClientConfig for (&AuthenticatorGlobalConfig, &AuthenticatorClientConfig) {
     fn tls_ca_certificates(&self) -> Vec<String> {
         self.1
             .tls_ca_certificates
-            .clone()
-            .unwrap_or_else(|| self.0.tls_ca_certificates.clone())
-            .0
+            .as_ref()
+            .unwrap_or(&self.0.tls_ca_certificates)
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect()
     }
 }
 
@@ -154,6 +219,39 @@ impl Authenticator {
         Self::from_configs(config.global, configs).await
     }
 
+    /// Create an authenticator from a reloadable configuration.
+    ///
+    /// Returns a handle to the current authenticator, which is [`None`] if the configuration
+    /// disables it, and a future re-creating it whenever the configuration changes. Changes are
+    /// only applied while the future is running. If creating the new clients fails, the previous
+    /// authenticator is kept.
+    pub async fn reloadable(
+        mut config: Reloadable<AuthenticatorConfig>,
+    ) -> anyhow::Result<(
+        Reloadable<Option<Self>>,
+        impl Future<Output = anyhow::Result<()>>,
+    )> {
+        let current = AuthenticatorConfig::clone(&config.current())
+            .into_client()
+            .await?;
+        let (sender, authenticator) = Reloadable::channel(current);
+
+        let reload = async move {
+            while let Some(config) = config.changed().await {
+                match AuthenticatorConfig::clone(&config).into_client().await {
+                    Ok(authenticator) => {
+                        log::info!("Applying new OpenID clients: {authenticator:?}");
+                        sender.send_replace(Arc::new(authenticator));
+                    }
+                    Err(err) => log::warn!("Keeping previous OpenID clients: {err}"),
+                }
+            }
+            Ok(())
+        };
+
+        Ok((authenticator, reload))
+    }
+
     pub async fn from_configs<I>(
         global: AuthenticatorGlobalConfig,
         configs: I,
@@ -175,6 +273,13 @@ impl Authenticator {
         Ok(Self::from_clients(clients))
     }
 
+    /// Iterate over all clients, with their configuration name.
+    pub fn clients(&self) -> impl Iterator<Item = (&str, &Client<Discovered, ExtendedClaims>)> {
+        self.clients
+            .iter()
+            .map(|(name, client)| (name.as_str(), client))
+    }
+
     /// Find a client by its configuration name.
     ///
     /// This is a brute force search and shouldn't be called that often.
@@ -301,6 +406,7 @@ pub async fn create_client<C: ClientConfig, P: CompactJson + Claims>(
 mod test {
 
     use super::*;
+    use crate::core::config::testing::from_reference;
     use crate::core::config::ConfigFromEnv;
     use openid::biscuit::ClaimsSet;
 
@@ -332,6 +438,25 @@ mod test {
         AuthenticatorConfig::from_env().expect("Empty config is ok");
     }
 
+    #[test]
+    fn test_reference() {
+        let reference = Reference::new(None).section::<AuthenticatorConfig>("oauth");
+
+        let entry = reference
+            .entries()
+            .iter()
+            .find(|entry| entry.env_var == "OAUTH__CLIENTS__<NAME>__CLIENT_ID")
+            .expect("Must contain client ID");
+        assert!(!entry.required);
+
+        let entry = reference
+            .entries()
+            .iter()
+            .find(|entry| entry.env_var == "OAUTH__ISSUER_URL")
+            .expect("Must contain global issuer URL");
+        assert!(!entry.required);
+    }
+
     #[test]
     fn test_standard_config() {
         #[derive(Deserialize)]
@@ -376,4 +501,40 @@ mod test {
             })
         );
     }
+
+    #[test]
+    fn test_reference() {
+        let config: AuthenticatorConfig = from_reference(&[]);
+
+        assert!(!config.disabled);
+        assert_eq!(
+            config.global.issuer_url.as_deref(),
+            Some("http://localhost")
+        );
+        assert_eq!(
+            config.clients.get("sample").map(|c| c.client_id.as_str()),
+            Some("sample")
+        );
+    }
+
+    #[test]
+    fn test_reference_global() {
+        let config: AuthenticatorGlobalConfig = from_reference(&[]);
+
+        assert_eq!(config.issuer_url.as_deref(), Some("http://localhost"));
+        assert_eq!(config.redirect_url.as_deref(), Some("http://localhost"));
+        assert_eq!(*config.tls_ca_certificates, vec![PathBuf::from("sample")]);
+    }
+
+    #[test]
+    fn test_reference_client() {
+        let config: AuthenticatorClientConfig = from_reference(&[]);
+
+        assert_eq!(config.client_id, "sample");
+        assert_eq!(config.client_secret.expose(), "sample");
+        assert_eq!(config.scopes, defaults::oauth2_scopes());
+        assert_eq!(config.issuer_url.as_deref(), Some("http://localhost"));
+        assert_eq!(config.tls_insecure, Some(true));
+        assert!(config.tls_ca_certificates.is_some());
+    }
 }
@@ -1,8 +1,12 @@
-use crate::{core::config::CommaSeparatedVec, reqwest::ClientFactory};
+use crate::{
+    core::config::{ConfigReference, Delimited, Reference, Secret},
+    reqwest::ClientFactory,
+};
 use anyhow::Context;
 use core::fmt::Debug;
 use drogue_client::openid::OpenIdTokenProvider;
 use serde::Deserialize;
+use std::path::PathBuf;
 use std::time::Duration;
 use url::Url;
 
@@ -11,7 +15,7 @@ use url::Url;
 pub struct TokenConfig {
     pub client_id: String,
 
-    pub client_secret: String,
+    pub client_secret: Secret<String>,
 
     pub issuer_url: Url,
 
@@ -19,17 +23,41 @@ pub struct TokenConfig {
     pub tls_insecure: bool,
 
     #[serde(default)]
-    pub tls_ca_certificates: CommaSeparatedVec,
+    pub tls_ca_certificates: Delimited<PathBuf>,
 
     #[serde(default)]
     #[serde(with = "humantime_serde")]
     pub refresh_before: Option<Duration>,
 }
 
+impl ConfigReference for TokenConfig {
+    fn describe(r: &mut Reference) {
+        r.required("client_id", "string", "The OAuth2 client ID");
+        r.required("client_secret", "secret", "The OAuth2 client secret");
+        r.required("issuer_url", "url", "The issuer URL");
+        r.with_default(
+            "tls_insecure",
+            "boolean",
+            false,
+            "Disable TLS validation, do not use this in production",
+        );
+        r.optional(
+            "tls_ca_certificates",
+            "comma separated list",
+            "Additional CA certificates (PEM files)",
+        );
+        r.optional(
+            "refresh_before",
+            "duration",
+            "Refresh the token this amount of time before it expires, defaults to 15 seconds",
+        );
+    }
+}
+
 impl TokenConfig {
     pub async fn into_client(self, redirect: Option<String>) -> anyhow::Result<openid::Client> {
         let mut client = ClientFactory::new();
-        client = client.add_ca_certs(self.tls_ca_certificates.0);
+        client = client.add_ca_certs(self.tls_ca_certificates.into_inner());
 
         if self.tls_insecure {
             client = client.make_insecure();
@@ -38,7 +66,7 @@ impl TokenConfig {
         openid::Client::discover_with_client(
             client.build()?,
             self.client_id,
-            self.client_secret,
+            self.client_secret.into_inner(),
             redirect,
             self.issuer_url,
         )
@@ -63,6 +91,7 @@ impl TokenConfig {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::core::config::testing::from_reference;
     use crate::core::config::ConfigFromEnv;
     use std::collections::HashMap;
 
@@ -81,11 +110,11 @@ mod test {
         assert_eq!(
             TokenConfig {
                 client_id: "id".to_string(),
-                client_secret: "secret".to_string(),
+                client_secret: "secret".into(),
                 issuer_url: Url::parse("http://foo.bar/baz/buz").unwrap(),
                 refresh_before: None,
                 tls_insecure: false,
-                tls_ca_certificates: vec!["/foo/bar/baz".to_string()].into(),
+                tls_ca_certificates: vec![PathBuf::from("/foo/bar/baz")].into(),
             },
             config
         );
@@ -106,14 +135,28 @@ mod test {
         assert_eq!(
             TokenConfig {
                 client_id: "id".to_string(),
-                client_secret: "secret".to_string(),
+                client_secret: "secret".into(),
                 issuer_url: Url::parse("http://foo.bar/baz/buz").unwrap(),
                 refresh_before: None,
                 tls_insecure: false,
-                tls_ca_certificates: vec!["/foo/bar/baz".to_string(), "/foo/bar/baz2".to_string()]
-                    .into(),
+                tls_ca_certificates: vec![
+                    PathBuf::from("/foo/bar/baz"),
+                    PathBuf::from("/foo/bar/baz2")
+                ]
+                .into(),
             },
             config
         );
     }
+
+    #[test]
+    fn test_reference() {
+        let config: TokenConfig = from_reference(&[]);
+
+        assert_eq!(config.client_id, "sample");
+        assert_eq!(config.client_secret.expose(), "sample");
+        assert_eq!(config.issuer_url.as_str(), "http://localhost/");
+        assert_eq!(*config.tls_ca_certificates, vec![PathBuf::from("sample")]);
+        assert_eq!(config.refresh_before, Some(Duration::from_secs(1)));
+    }
 }
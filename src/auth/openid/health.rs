@@ -0,0 +1,67 @@
+use crate::{
+    auth::openid::Authenticator,
+    core::config::Reloadable,
+    health::{Criticality, HealthCheckError, HealthChecked},
+};
+use async_trait::async_trait;
+
+/// A health check, verifying that the issuers of an [`Authenticator`] can be reached.
+///
+/// For each client, the key set of its issuer will be fetched. As previously fetched keys can
+/// still be used for validating tokens, a failure only reports the application as degraded.
+pub struct IssuerHealthCheck {
+    authenticator: Reloadable<Option<Authenticator>>,
+}
+
+impl IssuerHealthCheck {
+    pub fn new(authenticator: &Authenticator) -> Self {
+        Self::reloadable(Reloadable::fixed(Some(authenticator.clone())))
+    }
+
+    /// Create a health check, which follows changes of the authenticator.
+    ///
+    /// Also see [`Authenticator::reloadable`].
+    pub fn reloadable(authenticator: Reloadable<Option<Authenticator>>) -> Self {
+        Self { authenticator }
+    }
+}
+
+#[async_trait]
+impl HealthChecked for IssuerHealthCheck {
+    fn name(&self) -> String {
+        "openid-issuers".into()
+    }
+
+    fn criticality(&self) -> Criticality {
+        Criticality::NonCritical
+    }
+
+    async fn is_ready(&self) -> Result<(), HealthCheckError> {
+        let authenticator = self.authenticator.current();
+        let clients = authenticator.iter().flat_map(|authenticator| {
+            authenticator.clients().map(|(name, client)| {
+                (
+                    name.to_string(),
+                    client.http_client.clone(),
+                    client.provider.config().jwks_uri.clone(),
+                )
+            })
+        });
+        let endpoints: Vec<_> = clients.collect();
+
+        for (name, client, url) in endpoints {
+            if let Err(err) = client
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+            {
+                return HealthCheckError::nok(format!(
+                    "Issuer of client '{name}' is not reachable: {err}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -2,10 +2,12 @@
 
 mod authenticator;
 mod config;
+mod health;
 mod validate;
 
 pub use self::config::*;
 pub use authenticator::*;
+pub use health::*;
 pub use openid::CustomClaims;
 
 use drogue_client::user::v1::UserDetails;
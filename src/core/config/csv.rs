@@ -1,6 +1,16 @@
+use super::{parse, split, Comma, Element};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{hash_map::Entry, HashMap};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
 
 /// A way to use comma seperated values in a config structure.
+///
+/// For typed values, escaping, or accepting sequences from configuration files, see
+/// [`super::Delimited`].
 #[derive(Clone, Debug, PartialEq, Eq, Default, serde::Deserialize)]
 #[serde(from = "String")]
 pub struct CommaSeparatedVec(pub Vec<String>);
@@ -30,3 +40,288 @@ impl From<String> for CommaSeparatedVec {
         Self(value.split(',').map(|s| s.into()).collect::<Vec<String>>())
     }
 }
+
+/// A way to use comma separated key/value pairs (e.g. `a=1,b=2`) in a config structure.
+///
+/// Keys and values are parsed using their [`FromStr`] implementation. Entries are split using
+/// the same rules as [`super::Delimited`], so a comma can be escaped using a backslash. Keys and
+/// values are separated by the first `=` of an entry, so values may contain additional `=`
+/// characters. An `=` which is part of the key can be escaped using a backslash. Duplicate keys
+/// are rejected.
+///
+/// When deserializing, an actual map (e.g. from a configuration file) is accepted as well.
+///
+/// ```
+/// use drogue_bazaar::core::config::{CommaSeparatedMap, ConfigFromEnv};
+/// use std::collections::HashMap;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     labels: CommaSeparatedMap<String, String>,
+/// }
+///
+/// let mut env = HashMap::new();
+/// env.insert("LABELS", "app=foo, tier=backend");
+///
+/// let config = Config::from_set(env).unwrap();
+/// assert_eq!(config.labels["app"], "foo");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommaSeparatedMap<K: Hash + Eq, V>(pub HashMap<K, V>);
+
+impl<K: Hash + Eq, V> Default for CommaSeparatedMap<K, V> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+
+impl<K: Hash + Eq, V> CommaSeparatedMap<K, V> {
+    pub fn into_inner(self) -> HashMap<K, V> {
+        self.0
+    }
+}
+
+impl<K: Hash + Eq, V> Deref for CommaSeparatedMap<K, V> {
+    type Target = HashMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K: Hash + Eq, V> DerefMut for CommaSeparatedMap<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<K: Hash + Eq, V> From<HashMap<K, V>> for CommaSeparatedMap<K, V> {
+    fn from(values: HashMap<K, V>) -> Self {
+        Self(values)
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for CommaSeparatedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Insert an entry, failing if the key is already present.
+fn insert_unique<K, V, E>(map: &mut HashMap<K, V>, key: K, value: V) -> Result<(), E>
+where
+    K: Hash + Eq + Display,
+    E: de::Error,
+{
+    match map.entry(key) {
+        Entry::Occupied(entry) => Err(E::custom(format_args!("duplicate key '{}'", entry.key()))),
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+            Ok(())
+        }
+    }
+}
+
+/// Split an entry at the first unescaped `=`, unescaping the key.
+fn split_entry(entry: &str) -> Option<(String, &str)> {
+    let mut key = String::new();
+
+    let mut chars = entry.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, next)) if next == '=' || next == '\\' => key.push(next),
+                Some((_, next)) => {
+                    key.push(c);
+                    key.push(next);
+                }
+                None => key.push(c),
+            },
+            '=' => return Some((key, &entry[i + 1..])),
+            c => key.push(c),
+        }
+    }
+
+    None
+}
+
+impl<K, V> FromStr for CommaSeparatedMap<K, V>
+where
+    K: FromStr + Hash + Eq + Display,
+    K::Err: Display,
+    V: FromStr,
+    V::Err: Display,
+{
+    type Err = de::value::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut values = HashMap::new();
+
+        for entry in split::<Comma>(s) {
+            let (key, value) = split_entry(&entry).ok_or_else(|| {
+                de::Error::custom(format_args!(
+                    "invalid entry '{entry}', expected 'key=value'"
+                ))
+            })?;
+            let key: K = parse(key.trim())?;
+            let value = V::from_str(value.trim()).map_err(|err| {
+                de::Error::custom(format_args!("invalid value for key '{key}': {err}"))
+            })?;
+            insert_unique(&mut values, key, value)?;
+        }
+
+        Ok(Self(values))
+    }
+}
+
+impl<K, V> Display for CommaSeparatedMap<K, V>
+where
+    K: Hash + Eq + Display,
+    V: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // sort entries, to get a stable output
+        let mut entries = self
+            .0
+            .iter()
+            .map(|(k, v)| {
+                let k = k.to_string().replace('\\', "\\\\").replace('=', "\\=");
+                format!("{k}={v}").replace('\\', "\\\\").replace(',', "\\,")
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+
+        f.write_str(&entries.join(","))
+    }
+}
+
+impl<K, V> Serialize for CommaSeparatedMap<K, V>
+where
+    K: Hash + Eq + Display,
+    V: Display,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for CommaSeparatedMap<K, V>
+where
+    K: FromStr + Hash + Eq + Display,
+    K::Err: Display,
+    V: FromStr,
+    V::Err: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MapVisitor<K, V>(PhantomData<(K, V)>);
+
+        impl<'de, K, V> de::Visitor<'de> for MapVisitor<K, V>
+        where
+            K: FromStr + Hash + Eq + Display,
+            K::Err: Display,
+            V: FromStr,
+            V::Err: Display,
+        {
+            type Value = CommaSeparatedMap<K, V>;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                f.write_str("a map, or a string of comma separated 'key=value' pairs")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                CommaSeparatedMap::from_str(v).map_err(E::custom)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut values = HashMap::with_capacity(map.size_hint().unwrap_or_default());
+                while let Some((Element(key), Element(value))) = map.next_entry()? {
+                    insert_unique(&mut values, key, value)?;
+                }
+                Ok(CommaSeparatedMap(values))
+            }
+        }
+
+        deserializer.deserialize_any(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::{testing::TempDir, ConfigFromEnv, ConfigSources};
+    use config::Environment;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        #[serde(default)]
+        ports: CommaSeparatedMap<String, u16>,
+    }
+
+    fn load(value: &str) -> Result<Config, config::ConfigError> {
+        let mut env = HashMap::new();
+        env.insert("PORTS", value);
+        Config::from_set(env)
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = load("http=80, https = 443,").unwrap();
+
+        assert_eq!(config.ports.len(), 2);
+        assert_eq!(config.ports["http"], 80);
+        assert_eq!(config.ports["https"], 443);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = load("http=80,https").unwrap_err().to_string();
+        assert!(err.contains("invalid entry 'https'"), "{err}");
+
+        let err = load("http=80,https=foo").unwrap_err().to_string();
+        assert!(err.contains("invalid value for key 'https'"), "{err}");
+
+        let err = load("http=80,http=8080").unwrap_err().to_string();
+        assert!(err.contains("duplicate key 'http'"), "{err}");
+    }
+
+    #[test]
+    fn test_map() {
+        let dir = TempDir::new("csv-map");
+        let path = dir.join("config.yaml");
+        std::fs::write(&path, "ports:\n  http: 80\n  https: \"443\"\n").unwrap();
+
+        let config: Config = ConfigSources::new()
+            .file(&path)
+            .environment(Environment::default().source(Some(Default::default())))
+            .load()
+            .unwrap();
+
+        assert_eq!(config.ports.len(), 2);
+        assert_eq!(config.ports["https"], 443);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let map: CommaSeparatedMap<String, String> = "b=2,a=x\\,y=z".parse().unwrap();
+        assert_eq!(map["a"], "x,y=z");
+
+        let json = serde_json::to_value(&map).unwrap();
+        assert_eq!(json, serde_json::json!("a=x\\,y=z,b=2"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let map: CommaSeparatedMap<String, String> = [
+            ("a=b", "c=d"),
+            ("e,f", "g,h"),
+            ("i\\j", "k\\,l"),
+            ("m\\=", "=n\\"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let parsed: CommaSeparatedMap<String, String> = map.to_string().parse().unwrap();
+        assert_eq!(parsed, map);
+    }
+}
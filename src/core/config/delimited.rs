@@ -0,0 +1,466 @@
+use core::fmt::{self, Debug, Display, Formatter};
+use core::marker::PhantomData;
+use core::str::FromStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::ops::{Deref, DerefMut};
+
+/// A separator for [`Delimited`] values.
+///
+/// Implementing this trait allows to define custom separators, and to change how elements are
+/// processed.
+pub trait Separator {
+    /// The character separating the elements.
+    const SEPARATOR: char;
+    /// Remove leading and trailing whitespace from elements.
+    const TRIM: bool = true;
+    /// Skip elements which are empty (after trimming).
+    const SKIP_EMPTY: bool = true;
+}
+
+/// Separate elements by a comma (`,`).
+#[derive(Clone, Copy, Debug)]
+pub struct Comma;
+
+impl Separator for Comma {
+    const SEPARATOR: char = ',';
+}
+
+/// Separate elements by a semicolon (`;`).
+#[derive(Clone, Copy, Debug)]
+pub struct Semicolon;
+
+impl Separator for Semicolon {
+    const SEPARATOR: char = ';';
+}
+
+/// Separate elements by a space (` `).
+#[derive(Clone, Copy, Debug)]
+pub struct Space;
+
+impl Separator for Space {
+    const SEPARATOR: char = ' ';
+}
+
+/// A list of typed values, which can be provided as a single, delimited string.
+///
+/// Each element is parsed using its [`FromStr`] implementation. A separator can be part of an
+/// element by escaping it with a backslash (e.g. `a\,b`), a backslash itself can be escaped
+/// using a double backslash. By default, elements are trimmed and empty elements are skipped,
+/// which can be changed by using a custom [`Separator`].
+///
+/// When deserializing, an actual sequence (e.g. from a configuration file) is accepted as well.
+/// When serializing, the elements will be joined into a delimited string again.
+///
+/// Types without a [`FromStr`] implementation may have a wrapper type which does, like
+/// [`humantime::Duration`] for [`std::time::Duration`].
+///
+/// ```
+/// use drogue_bazaar::core::config::{ConfigFromEnv, Delimited};
+/// use std::{collections::HashMap, net::SocketAddr};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     peers: Delimited<SocketAddr>,
+/// }
+///
+/// let mut env = HashMap::new();
+/// env.insert("PEERS", "127.0.0.1:8080, 127.0.0.1:8081");
+///
+/// let config = Config::from_set(env).unwrap();
+/// assert_eq!(config.peers.len(), 2);
+/// ```
+pub struct Delimited<T, S = Comma> {
+    values: Vec<T>,
+    _marker: PhantomData<fn() -> S>,
+}
+
+/// A list of values, separated by a comma.
+pub type CommaSeparated<T> = Delimited<T, Comma>;
+
+impl<T, S> Delimited<T, S> {
+    pub fn new(values: Vec<T>) -> Self {
+        Self {
+            values,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T: Clone, S> Clone for Delimited<T, S> {
+    fn clone(&self) -> Self {
+        Self::new(self.values.clone())
+    }
+}
+
+impl<T: Debug, S> Debug for Delimited<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.values.fmt(f)
+    }
+}
+
+impl<T: PartialEq, S> PartialEq for Delimited<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl<T: Eq, S> Eq for Delimited<T, S> {}
+
+impl<T, S> Default for Delimited<T, S> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T, S> Deref for Delimited<T, S> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.values
+    }
+}
+
+impl<T, S> DerefMut for Delimited<T, S> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.values
+    }
+}
+
+impl<T, S> From<Vec<T>> for Delimited<T, S> {
+    fn from(values: Vec<T>) -> Self {
+        Self::new(values)
+    }
+}
+
+impl<T, S> FromIterator<T> for Delimited<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl<T, S> IntoIterator for Delimited<T, S> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a Delimited<T, S> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.iter()
+    }
+}
+
+/// Split a string into its elements, handling escaped characters.
+pub(crate) fn split<S: Separator>(value: &str) -> Vec<String> {
+    let mut elements = Vec::new();
+    let mut current = String::new();
+
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == S::SEPARATOR || next == '\\' => current.push(next),
+                Some(next) => {
+                    current.push(c);
+                    current.push(next);
+                }
+                None => current.push(c),
+            },
+            c if c == S::SEPARATOR => elements.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    elements.push(current);
+
+    elements
+        .into_iter()
+        .map(|element| match S::TRIM {
+            true => element.trim().to_string(),
+            false => element,
+        })
+        .filter(|element| !(S::SKIP_EMPTY && element.is_empty()))
+        .collect()
+}
+
+impl<T: FromStr, S: Separator> FromStr for Delimited<T, S> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        split::<S>(s)
+            .iter()
+            .map(|element| T::from_str(element))
+            .collect()
+    }
+}
+
+impl<T: Display, S: Separator> Display for Delimited<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", S::SEPARATOR)?;
+            }
+            for c in value.to_string().chars() {
+                if c == S::SEPARATOR || c == '\\' {
+                    write!(f, "\\")?;
+                }
+                write!(f, "{c}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Display, S: Separator> Serialize for Delimited<T, S> {
+    fn serialize<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Parse a value, mapping the error into a deserialization error.
+pub(crate) fn parse<T, E>(value: &str) -> Result<T, E>
+where
+    T: FromStr,
+    T::Err: Display,
+    E: de::Error,
+{
+    T::from_str(value).map_err(|err| E::custom(format_args!("invalid value '{value}': {err}")))
+}
+
+/// A single element of a sequence.
+///
+/// Sources may already have converted the value into a non-string type (like a number), so
+/// we need to accept those types too.
+pub(crate) struct Element<T>(pub(crate) T);
+
+impl<'de, T> Deserialize<'de> for Element<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ElementVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> de::Visitor<'de> for ElementVisitor<T>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            type Value = Element<T>;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a string, number, or boolean")
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                parse(&v.to_string()).map(Element)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                parse(&v.to_string()).map(Element)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                parse(&v.to_string()).map(Element)
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                parse(&v.to_string()).map(Element)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                parse(v).map(Element)
+            }
+        }
+
+        deserializer.deserialize_any(ElementVisitor(PhantomData))
+    }
+}
+
+impl<'de, T, S> Deserialize<'de> for Delimited<T, S>
+where
+    T: FromStr,
+    T::Err: Display,
+    S: Separator,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DelimitedVisitor<T, S>(PhantomData<(T, fn() -> S)>);
+
+        impl<T, S: Separator> DelimitedVisitor<T, S>
+        where
+            T: FromStr,
+            T::Err: Display,
+        {
+            fn parse_all<E: de::Error>(value: &str) -> Result<Delimited<T, S>, E> {
+                split::<S>(value).iter().map(|v| parse(v)).collect()
+            }
+        }
+
+        impl<'de, T, S> de::Visitor<'de> for DelimitedVisitor<T, S>
+        where
+            T: FromStr,
+            T::Err: Display,
+            S: Separator,
+        {
+            type Value = Delimited<T, S>;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a sequence, or a string of values separated by '{}'",
+                    S::SEPARATOR
+                )
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Self::parse_all(&v.to_string())
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Self::parse_all(&v.to_string())
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Self::parse_all(&v.to_string())
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Self::parse_all(&v.to_string())
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Self::parse_all(v)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut values = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(Element(value)) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(Delimited::new(values))
+            }
+        }
+
+        deserializer.deserialize_any(DelimitedVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::{testing::TempDir, ConfigFromEnv, ConfigSources};
+    use config::Environment;
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use url::Url;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        #[serde(default)]
+        urls: Delimited<Url>,
+        #[serde(default)]
+        ports: Delimited<u16, Semicolon>,
+        #[serde(default)]
+        names: Delimited<String>,
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut env = HashMap::new();
+        env.insert("URLS", "http://localhost:8080, https://example.com/");
+        env.insert("PORTS", "80;443;");
+        env.insert("NAMES", r#"a\,b,c\\,,d"#);
+
+        let config = Config::from_set(env).unwrap();
+
+        assert_eq!(
+            config.urls.into_inner(),
+            vec![
+                Url::parse("http://localhost:8080").unwrap(),
+                Url::parse("https://example.com").unwrap(),
+            ]
+        );
+        assert_eq!(*config.ports, vec![80, 443]);
+        assert_eq!(*config.names, vec!["a,b", "c\\", "d"]);
+    }
+
+    #[test]
+    fn test_single_number() {
+        // the environment source parses this into a number
+        let mut env = HashMap::new();
+        env.insert("PORTS", "8080");
+
+        let config = Config::from_set(env).unwrap();
+        assert_eq!(*config.ports, vec![8080]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut env = HashMap::new();
+        env.insert("NAMES", "");
+
+        let config = Config::from_set(env).unwrap();
+        assert!(config.names.is_empty());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut env = HashMap::new();
+        env.insert("URLS", "http://localhost,foo");
+
+        let err = Config::from_set(env).unwrap_err();
+        assert!(err.to_string().contains("invalid value 'foo'"), "{err}");
+    }
+
+    #[test]
+    fn test_sequence() {
+        let dir = TempDir::new("delimited");
+        let path = dir.join("config.yaml");
+        std::fs::write(
+            &path,
+            r#"
+ports:
+  - 80
+  - "443"
+names:
+  - a,b
+"#,
+        )
+        .unwrap();
+
+        let config: Config = ConfigSources::new()
+            .file(&path)
+            .environment(Environment::default().source(Some(HashMap::new())))
+            .load()
+            .unwrap();
+
+        assert_eq!(*config.ports, vec![80, 443]);
+        assert_eq!(*config.names, vec!["a,b"]);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let names: Delimited<String> =
+            vec!["a,b".to_string(), "c\\".to_string(), "d".to_string()].into();
+        let json = serde_json::to_value(&names).unwrap();
+        assert_eq!(json, serde_json::json!(r#"a\,b,c\\,d"#));
+
+        let parsed: Delimited<String> = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, names);
+
+        let addrs: Delimited<SocketAddr, Space> = "127.0.0.1:80  [::1]:443".parse().unwrap();
+        assert_eq!(addrs.to_string(), "127.0.0.1:80 [::1]:443");
+    }
+}
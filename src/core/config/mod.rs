@@ -1,6 +1,22 @@
 mod csv;
+mod delimited;
+mod reference;
+#[cfg(feature = "app")]
+mod reload;
+mod secret;
+mod sources;
+#[cfg(test)]
+pub(crate) mod testing;
+mod validate;
 
 pub use csv::*;
+pub use delimited::*;
+pub use reference::*;
+#[cfg(feature = "app")]
+pub use reload::*;
+pub use secret::*;
+pub use sources::*;
+pub use validate::*;
 
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -9,6 +25,9 @@ use std::collections::HashMap;
 ///
 /// The default setup uses `__` (double underscore) as a delimiter.
 ///
+/// Values can also be read from files, by appending `_FILE` (or `_FROM_FILE`) to the name of the
+/// variable. See [`FileIndirection`] for more information.
+///
 /// ```
 /// use drogue_bazaar::core::config::ConfigFromEnv;
 ///
@@ -75,7 +94,7 @@ impl<'de, T: Deserialize<'de> + Sized> ConfigFromEnv<'de> for T {
     fn from(env: config::Environment) -> Result<T, config::ConfigError> {
         let env = env.try_parsing(true).separator("__");
 
-        let cfg = config::Config::builder().add_source(env);
+        let cfg = config::Config::builder().add_source(FileIndirection::new(env));
         cfg.build()?.try_deserialize()
     }
 }
@@ -0,0 +1,280 @@
+use super::env_var;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// Describe the configuration values of a configuration structure.
+///
+/// This can be used to generate a reference of all environment variables an application
+/// accepts.
+///
+/// ```
+/// use drogue_bazaar::core::config::{ConfigReference, Reference};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     name: String,
+///     #[serde(default = "default_port")]
+///     port: u16,
+/// }
+///
+/// fn default_port() -> u16 {
+///     8080
+/// }
+///
+/// impl ConfigReference for Config {
+///     fn describe(r: &mut Reference) {
+///         r.required("name", "string", "The name of the instance");
+///         r.with_default("port", "integer", default_port(), "The port to listen on");
+///     }
+/// }
+///
+/// let reference = Reference::new(None).section::<Config>("app");
+/// assert_eq!(reference.entries()[0].env_var, "APP__NAME");
+/// ```
+pub trait ConfigReference {
+    fn describe(reference: &mut Reference);
+}
+
+/// A single configuration value of a [`Reference`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceEntry {
+    /// The full key of the value (e.g. `runtime.health.bind_addr`).
+    pub key: String,
+    /// The name of the environment variable.
+    pub env_var: String,
+    /// A short, human readable, name of the type.
+    pub r#type: String,
+    /// If the value is required.
+    pub required: bool,
+    /// The default value, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// A description of the value.
+    pub description: String,
+}
+
+/// A reference of configuration values.
+#[derive(Clone, Debug, Default)]
+pub struct Reference {
+    env_prefix: Option<String>,
+    path: Vec<String>,
+    /// The number of optional sections we are currently in.
+    optional: usize,
+    entries: Vec<ReferenceEntry>,
+}
+
+impl Reference {
+    /// Create a new reference, using an optional prefix for environment variables.
+    pub fn new(env_prefix: Option<&str>) -> Self {
+        Self {
+            env_prefix: env_prefix.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    /// Add all values of a type as a (required) section.
+    pub fn section<T: ConfigReference>(mut self, name: &str) -> Self {
+        self.nested::<T>(name);
+        self
+    }
+
+    /// Add all values of a type at the root level.
+    pub fn root<T: ConfigReference>(mut self) -> Self {
+        self.flatten::<T>();
+        self
+    }
+
+    /// The collected entries.
+    pub fn entries(&self) -> &[ReferenceEntry] {
+        &self.entries
+    }
+
+    fn push(
+        &mut self,
+        name: &str,
+        r#type: &str,
+        required: bool,
+        default: Option<String>,
+        description: &str,
+    ) {
+        let key = self
+            .path
+            .iter()
+            .map(|s| s.as_str())
+            .chain(std::iter::once(name))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        self.entries.push(ReferenceEntry {
+            env_var: env_var(self.env_prefix.as_deref(), &key),
+            key,
+            r#type: r#type.into(),
+            required: required && self.optional == 0,
+            default,
+            description: description.into(),
+        });
+    }
+
+    /// Add a required value.
+    pub fn required(&mut self, name: &str, r#type: &str, description: &str) {
+        self.push(name, r#type, true, None, description);
+    }
+
+    /// Add an optional value, without a default.
+    pub fn optional(&mut self, name: &str, r#type: &str, description: &str) {
+        self.push(name, r#type, false, None, description);
+    }
+
+    /// Add an optional value, with a default value.
+    pub fn with_default<D: Display>(
+        &mut self,
+        name: &str,
+        r#type: &str,
+        default: D,
+        description: &str,
+    ) {
+        self.push(name, r#type, false, Some(default.to_string()), description);
+    }
+
+    /// Add the values of a nested structure.
+    pub fn nested<T: ConfigReference>(&mut self, name: &str) {
+        self.path.push(name.into());
+        T::describe(self);
+        self.path.pop();
+    }
+
+    /// Add the values of an optional nested structure.
+    ///
+    /// Values of the nested structure are only required if the structure is present.
+    pub fn nested_optional<T: ConfigReference>(&mut self, name: &str) {
+        self.optional += 1;
+        self.nested::<T>(name);
+        self.optional -= 1;
+    }
+
+    /// Add the values of a map of structures, using a placeholder for the name of the entries.
+    ///
+    /// For a map named `clients` and the placeholder `name`, this will result in variables like
+    /// `CLIENTS__<NAME>__CLIENT_ID`.
+    pub fn map<T: ConfigReference>(&mut self, name: &str, placeholder: &str) {
+        self.path.push(name.into());
+        self.nested_optional::<T>(&format!("<{placeholder}>"));
+        self.path.pop();
+    }
+
+    /// Add the values of a flattened structure.
+    pub fn flatten<T: ConfigReference>(&mut self) {
+        T::describe(self);
+    }
+
+    /// Render the reference as a Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let escape = |s: &str| s.replace('|', "\\|");
+
+        let mut result = String::from(
+            "| Variable | Type | Required | Default | Description |\n|---|---|---|---|---|\n",
+        );
+
+        for entry in &self.entries {
+            result.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                entry.env_var,
+                escape(&entry.r#type),
+                if entry.required { "yes" } else { "no" },
+                entry
+                    .default
+                    .as_deref()
+                    .map(|d| format!("`{}`", escape(d)))
+                    .unwrap_or_default(),
+                escape(&entry.description)
+            ));
+        }
+
+        result
+    }
+
+    /// Render the reference as JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.entries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Foo;
+
+    impl ConfigReference for Foo {
+        fn describe(r: &mut Reference) {
+            r.required("name", "string", "The name");
+            r.with_default("port", "integer", 8080, "The port");
+            r.nested_optional::<Bar>("bar");
+            r.map::<Bar>("clients", "name");
+        }
+    }
+
+    struct Bar;
+
+    impl ConfigReference for Bar {
+        fn describe(r: &mut Reference) {
+            r.required("id", "string", "The ID");
+            r.optional("secret", "string", "The secret | password");
+        }
+    }
+
+    #[test]
+    fn test_reference() {
+        let reference = Reference::new(Some("APP")).section::<Foo>("foo");
+
+        assert_eq!(
+            reference
+                .entries()
+                .iter()
+                .map(|e| (e.env_var.as_str(), e.required))
+                .collect::<Vec<_>>(),
+            vec![
+                ("APP__FOO__NAME", true),
+                ("APP__FOO__PORT", false),
+                ("APP__FOO__BAR__ID", false),
+                ("APP__FOO__BAR__SECRET", false),
+                ("APP__FOO__CLIENTS__<NAME>__ID", false),
+                ("APP__FOO__CLIENTS__<NAME>__SECRET", false),
+            ]
+        );
+        assert_eq!(reference.entries()[4].key, "foo.clients.<name>.id");
+    }
+
+    #[test]
+    fn test_markdown() {
+        let reference = Reference::new(None).root::<Bar>();
+
+        assert_eq!(
+            reference.to_markdown(),
+            r#"| Variable | Type | Required | Default | Description |
+|---|---|---|---|---|
+| `ID` | string | yes |  | The ID |
+| `SECRET` | string | no |  | The secret \| password |
+"#
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let reference = Reference::new(None).root::<Foo>();
+        let json: serde_json::Value = serde_json::from_str(&reference.to_json().unwrap()).unwrap();
+
+        assert_eq!(
+            json[1],
+            serde_json::json!({
+                "key": "port",
+                "envVar": "PORT",
+                "type": "integer",
+                "required": false,
+                "default": "8080",
+                "description": "The port",
+            })
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use super::{ConfigSources, ValidationReport};
+use serde::de::DeserializeOwned;
+use std::{path::PathBuf, sync::Arc, time::Duration, time::SystemTime};
+use tokio::sync::watch;
+
+/// A handle to a configuration value, which may be reloaded.
+///
+/// Handles can be cloned, and all clones will see the same value.
+#[derive(Clone, Debug)]
+pub struct Reloadable<C> {
+    receiver: watch::Receiver<Arc<C>>,
+}
+
+impl<C> Reloadable<C> {
+    /// Create a handle to a fixed value, which will never change.
+    pub fn fixed(value: C) -> Self {
+        Self::channel(value).1
+    }
+
+    /// Create a handle, along with the sender to update its value.
+    pub(crate) fn channel(value: C) -> (watch::Sender<Arc<C>>, Self) {
+        let (sender, receiver) = watch::channel(Arc::new(value));
+        (sender, Self { receiver })
+    }
+
+    /// Get the current value.
+    pub fn current(&self) -> Arc<C> {
+        self.receiver.borrow().clone()
+    }
+
+    /// Wait for the value to change, and return the new value.
+    ///
+    /// Returns [`None`] if the value can no longer change, because the reloader is gone.
+    pub async fn changed(&mut self) -> Option<Arc<C>> {
+        match self.receiver.changed().await {
+            Ok(()) => Some(self.current()),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Reloads a configuration from its sources.
+///
+/// The configuration is reloaded when the process receives a `SIGHUP` signal (on Unix systems),
+/// or when one of the files (or secret directories) of the [`ConfigSources`] changes. A new
+/// configuration is only published if it is valid, otherwise the previous value is kept.
+///
+/// When using the `actix` feature, the CORS configuration of the HTTP server can follow changes
+/// by providing a reloadable configuration to `HttpBuilder::reloadable`. With the `auth` feature,
+/// `Authenticator::reloadable` re-creates the OpenID clients when their configuration changes. The
+/// log filter of the runtime (`runtime.log.filter`) is reloaded automatically.
+///
+/// ```
+/// use drogue_bazaar::core::config::{ConfigReloader, ConfigSources};
+/// use drogue_bazaar::app::{Startup, StartupExt};
+///
+/// #[derive(Debug, serde::Deserialize)]
+/// struct LogConfig {
+///     #[serde(default)]
+///     level: Option<String>,
+/// }
+///
+/// fn setup(startup: &mut dyn Startup) -> anyhow::Result<()> {
+///     let sources = match startup.config_sources() {
+///         Some(sources) => sources.clone(),
+///         None => return Ok(()),
+///     };
+///
+///     let reloader = ConfigReloader::<LogConfig>::new(sources, Some("log"))?;
+///     let mut config = reloader.subscribe();
+///
+///     startup.spawn(reloader.run());
+///     startup.spawn(async move {
+///         while let Some(config) = config.changed().await {
+///             log::info!("New log configuration: {config:?}");
+///         }
+///         Ok(())
+///     });
+///
+///     Ok(())
+/// }
+/// ```
+pub struct ConfigReloader<C> {
+    sources: ConfigSources,
+    section: Option<String>,
+    sender: watch::Sender<Arc<C>>,
+    poll_interval: Duration,
+}
+
+impl<C> ConfigReloader<C>
+where
+    C: DeserializeOwned + Send + Sync + 'static,
+{
+    /// Create a new reloader, loading the initial configuration from the sources.
+    ///
+    /// If a section is provided, the configuration will be loaded from this section. Also see
+    /// [`ConfigSources::validate_key`].
+    pub fn new(sources: ConfigSources, section: Option<&str>) -> Result<Self, ValidationReport> {
+        let section = section.map(Into::into);
+        let value = Self::load(&sources, &section)?;
+        let (sender, _) = watch::channel(Arc::new(value));
+
+        Ok(Self {
+            sources,
+            section,
+            sender,
+            poll_interval: Duration::from_secs(10),
+        })
+    }
+
+    /// Set the interval in which files are checked for changes.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Get a new handle to the configuration value.
+    pub fn subscribe(&self) -> Reloadable<C> {
+        Reloadable {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    fn load(sources: &ConfigSources, section: &Option<String>) -> Result<C, ValidationReport> {
+        match section {
+            Some(section) => sources.validate_key(section),
+            None => sources.validate(),
+        }
+    }
+
+    /// Reload the configuration, notifying all subscribers if the new configuration is valid.
+    pub fn reload(&self) -> Result<(), ValidationReport> {
+        let value = Self::load(&self.sources, &self.section)?;
+        self.sender.send_replace(Arc::new(value));
+        Ok(())
+    }
+
+    /// Get the modification timestamps of all files we monitor.
+    fn fingerprint(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+        self.sources
+            .paths()
+            .map(|path| {
+                let modified = std::fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok();
+                (path, modified)
+            })
+            .collect()
+    }
+
+    /// Run the reload loop.
+    ///
+    /// This will never return successfully, but can be dropped in order to stop reloading.
+    pub async fn run(self) -> anyhow::Result<()> {
+        #[cfg(unix)]
+        let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+
+        let mut interval = tokio::time::interval(self.poll_interval);
+        let mut fingerprint = self.fingerprint();
+
+        loop {
+            #[cfg(unix)]
+            tokio::select! {
+                _ = hangup.recv() => {
+                    log::info!("Received SIGHUP, reloading configuration");
+                }
+                _ = interval.tick() => {
+                    let current = self.fingerprint();
+                    if current == fingerprint {
+                        continue;
+                    }
+                    fingerprint = current;
+                    log::info!("Configuration files changed, reloading configuration");
+                }
+            }
+
+            #[cfg(not(unix))]
+            {
+                interval.tick().await;
+                let current = self.fingerprint();
+                if current == fingerprint {
+                    continue;
+                }
+                fingerprint = current;
+                log::info!("Configuration files changed, reloading configuration");
+            }
+
+            if let Err(report) = self.reload() {
+                log::warn!("Keeping previous configuration. {report}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::TempDir;
+    use config::Environment;
+    use futures_util::FutureExt;
+    use std::collections::HashMap;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Foo {
+        value: u32,
+    }
+
+    #[test]
+    fn test_reload() {
+        let dir = TempDir::new("reload");
+        let path = dir.join("config.yaml");
+        std::fs::write(&path, "foo:\n  value: 1\n").unwrap();
+
+        let sources = ConfigSources::new()
+            .file(&path)
+            .environment(Environment::default().source(Some(HashMap::new())));
+
+        let reloader = ConfigReloader::<Foo>::new(sources, Some("foo")).unwrap();
+        let mut handle = reloader.subscribe();
+        assert_eq!(handle.current().value, 1);
+
+        // valid change
+
+        std::fs::write(&path, "foo:\n  value: 2\n").unwrap();
+        reloader.reload().unwrap();
+
+        let changed = handle.changed().now_or_never().unwrap().unwrap();
+        assert_eq!(changed.value, 2);
+
+        // invalid change
+
+        std::fs::write(&path, "foo:\n  value: invalid\n").unwrap();
+        assert!(reloader.reload().is_err());
+
+        assert!(handle.changed().now_or_never().is_none());
+        assert_eq!(handle.current().value, 2);
+
+        // reloader gone
+
+        drop(reloader);
+        assert!(handle.changed().now_or_never().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fixed() {
+        let mut handle = Reloadable::fixed(Foo { value: 1 });
+        assert_eq!(handle.current().value, 1);
+        assert!(handle.changed().now_or_never().unwrap().is_none());
+    }
+}
@@ -0,0 +1,475 @@
+use config::{ConfigError, Map, Source, Value};
+use core::fmt::{Debug, Display, Formatter};
+use serde::{Deserialize, Serialize, Serializer};
+use std::path::{Path, PathBuf};
+
+/// The replacement text of a redacted value.
+pub(crate) const REDACTED: &str = "***";
+
+/// A secret value.
+///
+/// The value deserializes transparently, but will be redacted when being formatted using
+/// [`Debug`] or [`Display`], or when being serialized. Accessing the actual value requires an
+/// explicit call to [`Secret::expose`] or [`Secret::into_inner`].
+///
+/// ```
+/// use drogue_bazaar::core::config::Secret;
+///
+/// let secret = Secret::new("s3cr3t".to_string());
+///
+/// assert_eq!(format!("{secret:?}"), "***");
+/// assert_eq!(secret.expose(), "s3cr3t");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Get access to the secret value.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Unwrap the secret value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> Display for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> Serialize for Secret<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+/// The suffix of keys which point to a file containing the actual value, following the
+/// convention of Docker and Kubernetes.
+const FILE_SUFFIX: &str = "_file";
+
+/// An explicit alternative to [`FILE_SUFFIX`], which isn't ambiguous with regular fields.
+const FROM_FILE_SUFFIX: &str = "_from_file";
+
+/// Read a secret value from a file.
+///
+/// A single trailing newline will be removed, as it most likely is an artifact of the tooling
+/// which created the file.
+fn read_secret(path: &Path) -> std::io::Result<String> {
+    let mut value = std::fs::read_to_string(path)?;
+    if value.ends_with('\n') {
+        value.pop();
+        if value.ends_with('\r') {
+            value.pop();
+        }
+    }
+    Ok(value)
+}
+
+/// A source resolving `*_FILE` and `*_FROM_FILE` indirections of a wrapped source.
+///
+/// For every key ending with `_FILE` (e.g. `FOO__CLIENT_SECRET_FILE`), the value is interpreted as
+/// a path to a file, and the content of the file will be provided as the value of the key without
+/// the suffix (e.g. `FOO__CLIENT_SECRET`). This allows to provide secrets to an application
+/// without putting them into the process environment.
+///
+/// As regular configuration fields may end with `_file` too (like paths to certificates), such
+/// keys are kept, and a file which cannot be read is ignored. Using the explicit `_FROM_FILE`
+/// suffix instead removes the indirection key, and fails the collection of the source if the file
+/// cannot be read. In both cases, an explicit value of the key without the suffix takes
+/// precedence.
+#[derive(Clone, Debug)]
+pub struct FileIndirection<S> {
+    source: S,
+}
+
+impl<S> FileIndirection<S> {
+    pub fn new(source: S) -> Self {
+        Self { source }
+    }
+}
+
+impl<S> Source for FileIndirection<S>
+where
+    S: Source + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let mut values = self.source.collect()?;
+
+        let indirections = values
+            .keys()
+            .filter_map(|key| {
+                let (target, explicit) = match key.strip_suffix(FROM_FILE_SUFFIX) {
+                    Some(target) => (target, true),
+                    None => (key.strip_suffix(FILE_SUFFIX)?, false),
+                };
+                (!target.is_empty()).then(|| (key.clone(), target.to_string(), explicit))
+            })
+            .collect::<Vec<_>>();
+
+        for (key, target, explicit) in indirections {
+            let path = match explicit {
+                true => values.remove(&key).map(Value::into_string).transpose()?,
+                false => values
+                    .get(&key)
+                    .and_then(|path| path.clone().into_string().ok()),
+            };
+            let path = match path {
+                Some(path) => PathBuf::from(path),
+                None => continue,
+            };
+
+            if values.contains_key(&target) {
+                continue;
+            }
+
+            let value = match (read_secret(&path), explicit) {
+                (Ok(value), _) => value,
+                (Err(_), false) => continue,
+                (Err(err), true) => {
+                    return Err(ConfigError::Message(format!(
+                        "Failed to read value of '{target}' from file {path:?}: {err}"
+                    )))
+                }
+            };
+            let origin = path.display().to_string();
+            values.insert(target, Value::new(Some(&origin), value));
+        }
+
+        Ok(values)
+    }
+}
+
+/// A source reading values from a directory, where each file is a key and its content the value.
+///
+/// This matches the way Kubernetes mounts secrets into a container. Hidden files (starting with a
+/// dot) are skipped, which also skips the internal structure of Kubernetes mounts.
+///
+/// File names are processed like environment variables: they are converted to lowercase, and `__`
+/// (double underscore) is used as separator for nested values. Additionally, a dash (`-`) is
+/// converted into an underscore, as it is common in the names of secret keys.
+///
+/// ```
+/// use drogue_bazaar::core::config::{ConfigSources, SecretDirectory};
+///
+/// // a file named `client-secret` would provide the value `OAUTH__CLIENTS__FOO__CLIENT_SECRET`
+/// let sources = ConfigSources::new().secret_directory(
+///     SecretDirectory::new("/var/run/secrets/oauth")
+///         .prefix("OAUTH__CLIENTS__FOO")
+///         .required(false),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SecretDirectory {
+    path: PathBuf,
+    prefix: Option<String>,
+    required: bool,
+}
+
+impl SecretDirectory {
+    /// Create a new, required, secret directory source.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            prefix: None,
+            required: true,
+        }
+    }
+
+    /// Set a prefix, in the form of an environment variable (e.g. `OAUTH__CLIENTS__FOO`).
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Set if the directory is required, or if it will be skipped when missing.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// The path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn key(&self, name: &str) -> String {
+        let name = match &self.prefix {
+            Some(prefix) => format!("{prefix}__{name}"),
+            None => name.to_string(),
+        };
+
+        name.to_lowercase().replace('-', "_").replace("__", ".")
+    }
+}
+
+impl Source for SecretDirectory {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let mut values = Map::new();
+
+        let entries = match std::fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(err) if !self.required && err.kind() == std::io::ErrorKind::NotFound => {
+                log::debug!("Skipping missing secret directory: {:?}", self.path);
+                return Ok(values);
+            }
+            Err(err) => return Err(ConfigError::Foreign(Box::new(err))),
+        };
+
+        for entry in entries {
+            let entry = entry.map_err(|err| ConfigError::Foreign(Box::new(err)))?;
+            let name = entry.file_name();
+            let name = match name.to_str() {
+                Some(name) if !name.starts_with('.') => name,
+                _ => continue,
+            };
+
+            let path = entry.path();
+            // follow symlinks, which is how Kubernetes mounts those files
+            if !path.is_file() {
+                continue;
+            }
+
+            let value = read_secret(&path).map_err(|err| ConfigError::Foreign(Box::new(err)))?;
+            let origin = path.display().to_string();
+            values.insert(self.key(name), Value::new(Some(&origin), value));
+        }
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::{testing::TempDir, ConfigFromEnv, ConfigSources};
+    use config::Environment;
+    use std::collections::HashMap;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Foo {
+        client_id: String,
+        client_secret: String,
+        #[serde(default)]
+        key_file: Option<String>,
+    }
+
+    #[test]
+    fn test_secret() {
+        #[derive(Debug, serde::Deserialize, serde::Serialize)]
+        struct Config {
+            client_id: String,
+            client_secret: Secret<String>,
+            #[serde(default)]
+            password: Option<Secret<String>>,
+        }
+
+        let mut env = HashMap::<String, String>::new();
+        env.insert("FOO__CLIENT_ID".into(), "id".into());
+        env.insert("FOO__CLIENT_SECRET".into(), "s3cr3t".into());
+        env.insert("FOO__PASSWORD".into(), "pa55w0rd".into());
+
+        let config =
+            <Config as ConfigFromEnv>::from(Environment::with_prefix("FOO").source(Some(env)))
+                .unwrap();
+
+        assert_eq!(config.client_secret.expose(), "s3cr3t");
+        assert_eq!(
+            config.password.as_ref().map(|p| p.expose().as_str()),
+            Some("pa55w0rd")
+        );
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("s3cr3t"));
+        assert!(!debug.contains("pa55w0rd"));
+        assert_eq!(config.client_secret.to_string(), "***");
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "client_id": "id",
+                "client_secret": "***",
+                "password": "***",
+            })
+        );
+    }
+
+    #[test]
+    fn test_file_indirection() {
+        let dir = TempDir::new("file-indirection");
+        let secret = dir.join("secret");
+        std::fs::write(&secret, "s3cr3t\n").unwrap();
+
+        let mut env = HashMap::<String, String>::new();
+        env.insert("FOO__CLIENT_ID".into(), "id".into());
+        env.insert(
+            "FOO__CLIENT_SECRET_FILE".into(),
+            secret.to_string_lossy().into(),
+        );
+        env.insert("FOO__KEY_FILE".into(), secret.to_string_lossy().into());
+
+        let env = Environment::with_prefix("FOO")
+            .prefix_separator("__")
+            .source(Some(env));
+        let values = FileIndirection::new(env.clone()).collect().unwrap();
+        // regular fields ending with `_file` are kept
+        assert!(values.contains_key("client_secret_file"));
+        assert!(values.contains_key("key_file"));
+
+        let foo = <Foo as ConfigFromEnv>::from(env).unwrap();
+
+        assert_eq!(foo.client_id, "id");
+        assert_eq!(foo.client_secret, "s3cr3t");
+        assert_eq!(foo.key_file, Some(secret.to_string_lossy().into()));
+    }
+
+    #[test]
+    fn test_file_indirection_explicit() {
+        let dir = TempDir::new("file-indirection-explicit");
+        let secret = dir.join("secret");
+        std::fs::write(&secret, "s3cr3t\n").unwrap();
+
+        let mut env = HashMap::<String, String>::new();
+        env.insert("FOO__CLIENT_ID".into(), "id".into());
+        env.insert(
+            "FOO__CLIENT_SECRET_FROM_FILE".into(),
+            secret.to_string_lossy().into(),
+        );
+
+        let env = Environment::with_prefix("FOO")
+            .prefix_separator("__")
+            .source(Some(env));
+        let values = FileIndirection::new(env.clone()).collect().unwrap();
+        assert!(!values.contains_key("client_secret_from_file"));
+
+        let foo = <Foo as ConfigFromEnv>::from(env).unwrap();
+        assert_eq!(foo.client_secret, "s3cr3t");
+    }
+
+    #[test]
+    fn test_file_indirection_missing_file() {
+        let dir = TempDir::new("file-indirection-missing");
+
+        let mut env = HashMap::<String, String>::new();
+        env.insert("FOO__CLIENT_ID".into(), "id".into());
+        env.insert(
+            "FOO__CLIENT_SECRET_FROM_FILE".into(),
+            dir.join("missing").to_string_lossy().into(),
+        );
+
+        let source = FileIndirection::new(
+            Environment::with_prefix("FOO")
+                .prefix_separator("__")
+                .source(Some(env)),
+        );
+        let err = source.collect().unwrap_err();
+        assert!(err.to_string().contains("client_secret"));
+
+        // without the explicit suffix, the key is kept as it is
+        let mut env = HashMap::<String, String>::new();
+        env.insert(
+            "FOO__CLIENT_SECRET_FILE".into(),
+            dir.join("missing").to_string_lossy().into(),
+        );
+
+        let source = FileIndirection::new(
+            Environment::with_prefix("FOO")
+                .prefix_separator("__")
+                .source(Some(env)),
+        );
+        let values = source.collect().unwrap();
+        assert!(values.contains_key("client_secret_file"));
+        assert!(!values.contains_key("client_secret"));
+    }
+
+    #[test]
+    fn test_file_indirection_explicit_value() {
+        let dir = TempDir::new("file-indirection-explicit");
+        let secret = dir.join("secret");
+        std::fs::write(&secret, "from-file").unwrap();
+
+        let mut env = HashMap::<String, String>::new();
+        env.insert("FOO__CLIENT_ID".into(), "id".into());
+        env.insert("FOO__CLIENT_SECRET".into(), "explicit".into());
+        env.insert(
+            "FOO__CLIENT_SECRET_FROM_FILE".into(),
+            secret.to_string_lossy().into(),
+        );
+
+        let foo = <Foo as ConfigFromEnv>::from(Environment::with_prefix("FOO").source(Some(env)))
+            .unwrap();
+
+        assert_eq!(foo.client_secret, "explicit");
+    }
+
+    #[test]
+    fn test_secret_directory() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Config {
+            foo: Foo,
+        }
+
+        let dir = TempDir::new("secret-directory");
+        std::fs::write(dir.join("client-secret"), "s3cr3t").unwrap();
+        std::fs::write(dir.join(".hidden"), "hidden").unwrap();
+
+        let mut env = HashMap::<String, String>::new();
+        env.insert("FOO__CLIENT_ID".into(), "id".into());
+
+        let config: Config = ConfigSources::new()
+            .secret_directory(SecretDirectory::new(dir.path()).prefix("FOO"))
+            .environment(Environment::default().source(Some(env)))
+            .load()
+            .unwrap();
+
+        assert_eq!(config.foo.client_id, "id");
+        assert_eq!(config.foo.client_secret, "s3cr3t");
+    }
+
+    #[test]
+    fn test_missing_secret_directory() {
+        let source = SecretDirectory::new("/does/not/exist");
+        assert!(source.collect().is_err());
+
+        let source = source.required(false);
+        assert!(source.collect().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+use super::{validate, FileIndirection, SecretDirectory, ValidationReport};
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+
+/// Layered configuration sources.
+///
+/// Sources are applied in the following order, later sources override values of earlier ones:
+///
+/// * Configuration files, in the order they were added
+/// * Secret directories, in the order they were added
+/// * Environment variables
+/// * Programmatic overrides
+///
+/// Environment variables use the same semantics as [`super::ConfigFromEnv`], using `__` (double
+/// underscore) as a separator for nested values, and resolving `*_FILE` variables using
+/// [`FileIndirection`]. Configuration files use the format indicated by
+/// their file extension (e.g. `.yaml`, `.toml`, `.json`).
+///
+/// ```
+/// use drogue_bazaar::core::config::ConfigSources;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     #[serde(default)]
+///     my_str: String,
+/// }
+///
+/// fn run() -> anyhow::Result<()> {
+///     let env = std::env::var("ENVIRONMENT").unwrap_or_else(|_| "dev".into());
+///
+///     let config: Config = ConfigSources::new()
+///         .file("config/default.yaml")
+///         .optional_file(format!("config/{env}.yaml"))
+///         .set_override("my_str", "abc")
+///         .load()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConfigSources {
+    files: Vec<(PathBuf, bool)>,
+    secret_directories: Vec<SecretDirectory>,
+    environment: config::Environment,
+    env_prefix: Option<String>,
+    overrides: Vec<(String, config::Value)>,
+}
+
+impl ConfigSources {
+    /// Create a new set of sources, only containing environment variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a required configuration file.
+    pub fn file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.files.push((path.into(), true));
+        self
+    }
+
+    /// Add an optional configuration file, which will be skipped if it doesn't exist.
+    pub fn optional_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.files.push((path.into(), false));
+        self
+    }
+
+    /// Add a directory containing secrets, like a mounted Kubernetes secret.
+    pub fn secret_directory(mut self, directory: SecretDirectory) -> Self {
+        self.secret_directories.push(directory);
+        self
+    }
+
+    /// Set the environment variable source.
+    ///
+    /// The separator will always be set to `__`, any prefix set on the environment will be
+    /// respected. However, to report the correct variable names during validation, the prefix
+    /// should be set using [`Self::environment_prefix`].
+    pub fn environment(mut self, environment: config::Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Set the prefix of the environment variables, without the separator.
+    ///
+    /// The separator (`__`) gets added when matching variables, so a prefix of `APP` matches
+    /// variables like `APP__RUNTIME__HEALTH__ENABLED`.
+    pub fn environment_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        let prefix = prefix.into();
+        self.environment = self.environment.prefix(&prefix);
+        self.env_prefix = Some(prefix);
+        self
+    }
+
+    /// Override a value, using a path expression (e.g. `runtime.health.enabled`) as key.
+    ///
+    /// Overrides take precedence over all other sources. An invalid key will be reported when
+    /// building the configuration.
+    pub fn set_override<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<config::Value>,
+    {
+        self.overrides.push((key.into(), value.into()));
+        self
+    }
+
+    /// Build the layered configuration.
+    pub fn build(&self) -> Result<config::Config, config::ConfigError> {
+        let mut builder = config::Config::builder();
+
+        for (path, required) in &self.files {
+            builder = builder.add_source(config::File::from(path.as_path()).required(*required));
+        }
+
+        for directory in &self.secret_directories {
+            builder = builder.add_source(directory.clone());
+        }
+
+        builder = builder.add_source(FileIndirection::new(
+            self.environment.clone().try_parsing(true).separator("__"),
+        ));
+
+        for (key, value) in &self.overrides {
+            builder = builder.set_override(key.as_str(), value.clone())?;
+        }
+
+        builder.build()
+    }
+
+    /// Load the full configuration.
+    pub fn load<T: DeserializeOwned>(&self) -> Result<T, config::ConfigError> {
+        self.build()?.try_deserialize()
+    }
+
+    /// Load the configuration from a section of the configuration.
+    ///
+    /// If the section is missing, the type will be deserialized from an empty section, which
+    /// allows types consisting only of default values to be loaded.
+    pub fn load_key<T: DeserializeOwned>(&self, key: &str) -> Result<T, config::ConfigError> {
+        get_section(&self.build()?, key)
+    }
+
+    /// Load the full configuration, reporting all issues in case of a failure.
+    pub fn validate<T: DeserializeOwned>(&self) -> Result<T, ValidationReport> {
+        let config = self
+            .build()
+            .map_err(|err| ValidationReport::from_error(&err))?;
+        validate(&config, None, self.env_prefix.as_deref())
+    }
+
+    /// Load the configuration from a section, reporting all issues in case of a failure.
+    ///
+    /// Like [`Self::load_key`], a missing section will be treated as an empty section.
+    pub fn validate_key<T: DeserializeOwned>(&self, key: &str) -> Result<T, ValidationReport> {
+        let config = self
+            .build()
+            .map_err(|err| ValidationReport::from_error(&err))?;
+        validate(&config, Some(key), self.env_prefix.as_deref())
+    }
+
+    /// The paths of all files and directories used as a source.
+    pub fn paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        self.files.iter().map(|(path, _)| path.clone()).chain(
+            self.secret_directories
+                .iter()
+                .map(|d| d.path().to_path_buf()),
+        )
+    }
+
+    /// The prefix of environment variables, if one was set.
+    pub fn env_prefix(&self) -> Option<&str> {
+        self.env_prefix.as_deref()
+    }
+}
+
+/// Get a section of a configuration, defaulting to an empty one.
+pub(crate) fn get_section<T: DeserializeOwned>(
+    config: &config::Config,
+    key: &str,
+) -> Result<T, config::ConfigError> {
+    match config.get(key) {
+        Err(config::ConfigError::NotFound(_)) => T::deserialize(config::Value::new(
+            None,
+            config::Map::<String, config::Value>::new(),
+        )),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::testing::TempDir;
+    use config::Environment;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Foo {
+        bar: String,
+        baz: String,
+        #[serde(default)]
+        sub: Option<Sub>,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Sub {
+        value: u32,
+    }
+
+    fn write_file(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::File::create(&path)
+            .and_then(|mut f| f.write_all(content.as_bytes()))
+            .unwrap();
+        path
+    }
+
+    fn env(vars: &[(&str, &str)]) -> Environment {
+        let vars = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+        Environment::default().source(Some(vars))
+    }
+
+    #[test]
+    fn test_precedence() {
+        let dir = TempDir::new("sources");
+        let defaults = write_file(
+            &dir,
+            "defaults.yaml",
+            r#"
+bar: from-file
+baz: from-file
+sub:
+  value: 1
+"#,
+        );
+        let overlay = write_file(&dir, "overlay.yaml", "sub:\n  value: 2\n");
+
+        let foo: Foo = ConfigSources::new()
+            .file(&defaults)
+            .optional_file(&overlay)
+            .optional_file("/does/not/exist.yaml")
+            .environment(env(&[("BAR", "from-env"), ("BAZ", "from-env")]))
+            .set_override("baz", "from-override")
+            .load()
+            .unwrap();
+
+        assert_eq!(foo.bar, "from-env");
+        assert_eq!(foo.baz, "from-override");
+        assert_eq!(foo.sub.unwrap().value, 2);
+    }
+
+    #[test]
+    fn test_env_nested() {
+        let foo: Foo = ConfigSources::new()
+            .environment(env(&[("BAR", "a"), ("BAZ", "b"), ("SUB__VALUE", "42")]))
+            .load()
+            .unwrap();
+
+        assert_eq!(foo.bar, "a");
+        assert_eq!(foo.sub.unwrap().value, 42);
+    }
+
+    #[test]
+    fn test_required_file() {
+        let result = ConfigSources::new()
+            .file("/does/not/exist.yaml")
+            .environment(env(&[("BAR", "a"), ("BAZ", "b")]))
+            .load::<Foo>();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_missing_section() {
+        #[derive(Debug, Default, serde::Deserialize)]
+        struct Defaults {
+            #[serde(default)]
+            enabled: bool,
+        }
+
+        let sources = ConfigSources::new().environment(env(&[]));
+        let value: Defaults = sources.load_key("runtime").unwrap();
+        assert!(!value.enabled);
+
+        let sources = ConfigSources::new().environment(env(&[("RUNTIME__ENABLED", "true")]));
+        let value: Defaults = sources.load_key("runtime").unwrap();
+        assert!(value.enabled);
+    }
+}
@@ -0,0 +1,122 @@
+use super::{ConfigReference, Reference};
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary directory for tests, which gets removed when being dropped.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "{}-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+            name
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub(crate) fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Deserialize a configuration from the keys of its reference.
+///
+/// Every key gets its default value, or a sample value based on its type. Placeholders of maps
+/// (like `<name>`) are replaced with `sample`. Sample values can be overridden using their (full)
+/// key. This fails if a required field is missing from the reference, or a type doesn't match.
+pub(crate) fn from_reference<C>(overrides: &[(&str, config::Value)]) -> C
+where
+    C: ConfigReference + DeserializeOwned,
+{
+    let reference = Reference::new(None).root::<C>();
+
+    let mut builder = config::Config::builder();
+    for entry in reference.entries() {
+        let key = entry
+            .key
+            .split('.')
+            .map(|s| if s.starts_with('<') { "sample" } else { s })
+            .collect::<Vec<_>>()
+            .join(".");
+        let value = match overrides.iter().find(|(k, _)| *k == key) {
+            Some((_, value)) => value.clone(),
+            None => sample(&entry.r#type, entry.default.as_deref()),
+        };
+        builder = builder.set_override(key, value).unwrap();
+    }
+
+    builder
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .unwrap_or_else(|err| panic!("Failed to deserialize from reference: {err}"))
+}
+
+fn sample(r#type: &str, default: Option<&str>) -> config::Value {
+    match (r#type, default) {
+        ("boolean", Some(default)) => default.parse::<bool>().unwrap().into(),
+        ("boolean", None) => true.into(),
+        ("integer", Some(default)) => default.parse::<i64>().unwrap().into(),
+        ("integer", None) => 1i64.into(),
+        (_, Some(default)) => default.into(),
+        ("url", None) => "http://localhost".into(),
+        ("duration", None) => "1s".into(),
+        ("list", None) => vec!["sample"].into(),
+        (_, None) => "sample".into(),
+    }
+}
+
+/// Assert that the reference of a configuration matches its fields.
+///
+/// Compares the keys of the reference with the serialized default value, and verifies that a
+/// value can be deserialized from the reference.
+pub(crate) fn assert_reference<C>()
+where
+    C: ConfigReference + DeserializeOwned + Serialize + Default,
+{
+    fn collect(prefix: &str, value: &serde_json::Value, keys: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (name, value) in map {
+                    let key = match prefix {
+                        "" => name.clone(),
+                        _ => format!("{prefix}.{name}"),
+                    };
+                    collect(&key, value, keys);
+                }
+            }
+            _ => keys.push(prefix.to_string()),
+        }
+    }
+
+    let mut expected = vec![];
+    collect(
+        "",
+        &serde_json::to_value(C::default()).unwrap(),
+        &mut expected,
+    );
+    expected.sort();
+
+    let reference = Reference::new(None).root::<C>();
+    let mut actual: Vec<_> = reference.entries().iter().map(|e| e.key.clone()).collect();
+    actual.sort();
+
+    assert_eq!(actual, expected);
+
+    from_reference::<C>(&[]);
+}
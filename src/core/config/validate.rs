@@ -0,0 +1,415 @@
+use config::{ConfigError, Value, ValueKind};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+use std::fmt::{Display, Formatter};
+
+/// Limit the number of attempts to find further issues.
+const MAX_ATTEMPTS: usize = 256;
+
+/// A single issue found when validating a configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// The full key of the value (e.g. `runtime.health.bind_addr`), if known.
+    pub key: Option<String>,
+    /// The name of the environment variable providing the value, if known.
+    pub env_var: Option<String>,
+    /// The description of the problem.
+    pub message: String,
+}
+
+/// A report of all issues found when validating a configuration.
+///
+/// The [`Display`] implementation renders the issues as a table.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Create a report from a general configuration error, e.g. when building the configuration.
+    pub fn from_error(err: &ConfigError) -> Self {
+        Self {
+            issues: vec![ConfigIssue {
+                key: None,
+                env_var: None,
+                message: err.to_string(),
+            }],
+        }
+    }
+}
+
+impl Extend<ConfigIssue> for ValidationReport {
+    fn extend<T: IntoIterator<Item = ConfigIssue>>(&mut self, iter: T) {
+        self.issues.extend(iter)
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const HEADER: [&str; 3] = ["Key", "Environment variable", "Problem"];
+
+        let rows = self
+            .issues
+            .iter()
+            .map(|issue| {
+                [
+                    issue.key.as_deref().unwrap_or("-"),
+                    issue.env_var.as_deref().unwrap_or("-"),
+                    issue.message.as_str(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let mut widths = HEADER.map(|h| h.len());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        writeln!(f, "Invalid configuration ({} issues):", self.issues.len())?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "  {:w0$} | {:w1$} | {}",
+            HEADER[0],
+            HEADER[1],
+            HEADER[2],
+            w0 = widths[0],
+            w1 = widths[1]
+        )?;
+        writeln!(
+            f,
+            "  {}-+-{}-+-{}",
+            "-".repeat(widths[0]),
+            "-".repeat(widths[1]),
+            "-".repeat(widths[2])
+        )?;
+        for row in rows {
+            writeln!(
+                f,
+                "  {:w0$} | {:w1$} | {}",
+                row[0],
+                row[1],
+                row[2],
+                w0 = widths[0],
+                w1 = widths[1]
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+/// Values used to replace missing or invalid values, so that the validation can continue.
+fn placeholders() -> [ValueKind; 6] {
+    [
+        ValueKind::String(Default::default()),
+        ValueKind::Table(Default::default()),
+        ValueKind::Array(Default::default()),
+        ValueKind::Boolean(false),
+        ValueKind::I64(0),
+        ValueKind::Float(0.0),
+    ]
+}
+
+struct Patch {
+    key: String,
+    placeholder: usize,
+}
+
+impl Patch {
+    /// Check if the key is the patched key or a child of it.
+    fn covers(&self, key: &str) -> bool {
+        match key.strip_prefix(&self.key) {
+            Some(rest) => rest.is_empty() || rest.starts_with('.') || rest.starts_with('['),
+            None => false,
+        }
+    }
+}
+
+/// Validate a (section of a) configuration, collecting all issues.
+///
+/// Deserialization stops at the first error. To find further issues, a missing or invalid value is
+/// replaced with a placeholder value and the deserialization is repeated. This continues until the
+/// configuration deserializes or no placeholder value can be found. Issues of values nested under a
+/// missing value are not reported individually. The issues are sorted by their key.
+///
+/// Keys are converted into environment variables using the `__` (double underscore) separator, and
+/// the optional prefix.
+pub fn validate<T: DeserializeOwned>(
+    config: &config::Config,
+    section: Option<&str>,
+    env_prefix: Option<&str>,
+) -> Result<T, ValidationReport> {
+    let mut report = ValidationReport::default();
+    let mut patches = Vec::<Patch>::new();
+
+    for _ in 0..MAX_ATTEMPTS {
+        let (key, message, missing) = match deserialize(config, section, &patches) {
+            Ok(value) if report.is_empty() => return Ok(value),
+            Ok(_) => break,
+            Err(err) => err,
+        };
+
+        match patches.iter_mut().rev().find(|p| p.covers(&key)) {
+            Some(patch) if patch.key == key || !missing => {
+                // the placeholder didn't work, try the next one
+                patch.placeholder += 1;
+                if patch.placeholder >= placeholders().len() {
+                    break;
+                }
+            }
+            Some(_) => {
+                // missing value, nested under a missing value
+                patches.push(Patch {
+                    key,
+                    placeholder: 0,
+                });
+            }
+            None => {
+                let full_key = full_key(section, &key);
+                report.issues.push(ConfigIssue {
+                    env_var: full_key.as_deref().map(|key| env_var(env_prefix, key)),
+                    key: full_key,
+                    message,
+                });
+                patches.push(Patch {
+                    key,
+                    placeholder: 0,
+                });
+            }
+        }
+    }
+
+    report.issues.sort_by(|a, b| a.key.cmp(&b.key));
+
+    Err(report)
+}
+
+/// Try deserializing the configuration, with patches applied.
+///
+/// Returns the key, the message, and if the value was missing in case of an error.
+fn deserialize<T: DeserializeOwned>(
+    config: &config::Config,
+    section: Option<&str>,
+    patches: &[Patch],
+) -> Result<T, (String, String, bool)> {
+    let map_err = |err: ConfigError| (String::new(), err.to_string(), false);
+
+    let mut builder = config::Config::builder().add_source(config.clone());
+    for patch in patches {
+        let key = full_key(section, &patch.key).unwrap_or_default();
+        let value = placeholders()[patch.placeholder].clone();
+        builder = builder
+            .set_override(key, Value::new(None, value))
+            .map_err(map_err)?;
+    }
+    let config = builder.build().map_err(map_err)?;
+
+    let value: Value = match section {
+        Some(section) => match config.get(section) {
+            Err(ConfigError::NotFound(_)) => Value::new(None, ValueKind::Table(Default::default())),
+            result => result.map_err(map_err)?,
+        },
+        None => config.try_deserialize().map_err(map_err)?,
+    };
+
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let mut key = path_to_key(err.path().iter());
+        let err = err.into_inner();
+
+        let message = match &err {
+            ConfigError::Type {
+                unexpected,
+                expected,
+                ..
+            } => format!("invalid type: {unexpected}, expected {expected}"),
+            err => err.to_string(),
+        };
+
+        match missing_field(&message) {
+            Some(field) => {
+                if !key.is_empty() {
+                    key.push('.');
+                }
+                key.push_str(field);
+                (key, "missing value".into(), true)
+            }
+            None => (key, message, false),
+        }
+    })
+}
+
+/// Extract the name of a missing field from the error message.
+fn missing_field(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("missing field `")
+        .and_then(|s| s.strip_suffix('`'))
+}
+
+fn path_to_key<'a>(segments: impl Iterator<Item = &'a Segment>) -> String {
+    let mut key = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Seq { index } => key.push_str(&format!("[{index}]")),
+            Segment::Map { key: name } | Segment::Enum { variant: name } => {
+                if !key.is_empty() {
+                    key.push('.');
+                }
+                key.push_str(name);
+            }
+            Segment::Unknown => {}
+        }
+    }
+    key
+}
+
+fn full_key(section: Option<&str>, key: &str) -> Option<String> {
+    match (section, key.is_empty()) {
+        (Some(section), true) => Some(section.to_string()),
+        (Some(section), false) => Some(format!("{section}.{key}")),
+        (None, true) => None,
+        (None, false) => Some(key.to_string()),
+    }
+}
+
+/// Convert a key into the name of the environment variable.
+pub(crate) fn env_var(prefix: Option<&str>, key: &str) -> String {
+    let name = key.to_uppercase().replace('.', "__");
+    match prefix {
+        Some(prefix) => format!("{prefix}__{name}"),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::config::ConfigSources;
+    use config::Environment;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        #[serde(default)]
+        enabled: bool,
+        sub: Sub,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Sub {
+        value: String,
+        #[serde(default)]
+        other: Option<u32>,
+    }
+
+    fn sources(vars: &[(&str, &str)]) -> ConfigSources {
+        let vars = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<HashMap<_, _>>();
+        ConfigSources::new().environment(Environment::default().source(Some(vars)))
+    }
+
+    fn keys(report: &ValidationReport) -> Vec<(Option<&str>, &str)> {
+        report
+            .issues
+            .iter()
+            .map(|issue| (issue.env_var.as_deref(), issue.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid() {
+        let config: Config = sources(&[("NAME", "foo"), ("PORT", "8080"), ("SUB__VALUE", "bar")])
+            .validate()
+            .unwrap();
+
+        assert_eq!(config.name, "foo");
+        assert_eq!(config.port, 8080);
+        assert!(!config.enabled);
+        assert_eq!(config.sub.value, "bar");
+        assert_eq!(config.sub.other, None);
+    }
+
+    #[test]
+    fn test_all_missing() {
+        let report = sources(&[]).validate::<Config>().unwrap_err();
+
+        assert_eq!(
+            keys(&report),
+            vec![
+                (Some("NAME"), "missing value"),
+                (Some("PORT"), "missing value"),
+                (Some("SUB"), "missing value"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_and_missing() {
+        let report = sources(&[("PORT", "abc"), ("ENABLED", "maybe"), ("SUB__OTHER", "-1")])
+            .validate::<Config>()
+            .unwrap_err();
+
+        let keys = keys(&report);
+        assert_eq!(keys.len(), 5, "{report}");
+        assert_eq!(keys[0].0, Some("ENABLED"));
+        assert_eq!(keys[1], (Some("NAME"), "missing value"));
+        assert_eq!(keys[2].0, Some("PORT"));
+        assert_eq!(keys[3].0, Some("SUB__OTHER"));
+        assert_eq!(keys[4], (Some("SUB__VALUE"), "missing value"));
+    }
+
+    #[test]
+    fn test_section_with_prefix() {
+        #[derive(Debug, Deserialize)]
+        struct Runtime {
+            #[allow(unused)]
+            health: Sub,
+        }
+
+        let sources = sources(&[("APP__RUNTIME__HEALTH__OTHER", "1")]).environment_prefix("APP");
+        let report = sources.validate_key::<Runtime>("runtime").unwrap_err();
+
+        assert_eq!(
+            report.issues,
+            vec![ConfigIssue {
+                key: Some("runtime.health.value".into()),
+                env_var: Some("APP__RUNTIME__HEALTH__VALUE".into()),
+                message: "missing value".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let report = ValidationReport {
+            issues: vec![ConfigIssue {
+                key: Some("foo.bar".into()),
+                env_var: Some("FOO__BAR".into()),
+                message: "missing value".into(),
+            }],
+        };
+
+        assert_eq!(
+            report.to_string(),
+            r#"Invalid configuration (1 issues):
+
+  Key     | Environment variable | Problem
+  --------+----------------------+--------------
+  foo.bar | FOO__BAR             | missing value
+"#
+        );
+    }
+}
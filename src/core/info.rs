@@ -1,14 +1,21 @@
+use prometheus::{IntGauge, Opts, Registry};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
 /// Project information. Intended as information over all your project's components.
+#[derive(Clone, Copy, Debug, Serialize)]
 pub struct ProjectInformation {
     /// Project name
     pub name: &'static str,
     /// Version
     pub version: &'static str,
     /// Banner
+    #[serde(skip)]
     pub banner: &'static str,
 }
 
 /// Component information. Intended as information over all your project's components.
+#[derive(Clone, Copy, Debug, Serialize)]
 pub struct ComponentInformation {
     /// Project
     pub project: &'static ProjectInformation,
@@ -18,6 +25,147 @@ pub struct ComponentInformation {
     pub version: &'static str,
     /// Description
     pub description: &'static str,
+    /// Build information
+    pub build: BuildInformation,
+}
+
+impl ComponentInformation {
+    /// Register a `build_info` gauge with the registry.
+    ///
+    /// The gauge always has the value `1`, the information is provided as labels.
+    pub fn register_build_info(&self, registry: &Registry) -> prometheus::Result<()> {
+        let build = &self.build;
+        let opts = Opts::new("build_info", "Information about the component")
+            .const_label("project", self.project.name)
+            .const_label("component", self.name)
+            .const_label("version", self.version)
+            .const_label("git_commit", build.git_commit.unwrap_or_default())
+            .const_label(
+                "git_dirty",
+                build.git_dirty.map(|d| d.to_string()).unwrap_or_default(),
+            )
+            .const_label("build_timestamp", build.build_timestamp.unwrap_or_default())
+            .const_label("rustc_version", build.rustc_version.unwrap_or_default())
+            .const_label("features", build.features().collect::<Vec<_>>().join(","))
+            .const_label("target", build.target_triple.unwrap_or_default());
+
+        let gauge = IntGauge::with_opts(opts)?;
+        gauge.set(1);
+        registry.register(Box::new(gauge))
+    }
+}
+
+/// Build information, captured at compile time.
+///
+/// All fields are optional, as they are only available when being provided as environment
+/// variables during the build. The names of the variables are compatible with those generated by
+/// [vergen](https://crates.io/crates/vergen):
+///
+/// | Field             | Variable                     |
+/// | ----------------- | ---------------------------- |
+/// | `git_commit`      | `VERGEN_GIT_SHA`             |
+/// | `git_dirty`       | `VERGEN_GIT_DIRTY`           |
+/// | `build_timestamp` | `VERGEN_BUILD_TIMESTAMP`     |
+/// | `rustc_version`   | `VERGEN_RUSTC_SEMVER`        |
+/// | `cargo_features`  | `VERGEN_CARGO_FEATURES`      |
+/// | `target_triple`   | `VERGEN_CARGO_TARGET_TRIPLE` |
+///
+/// Without using vergen, the variables can also be provided by a build script of the component:
+///
+/// ```no_run
+/// // in the `main` function of the `build.rs` file
+/// let commit = std::process::Command::new("git")
+///     .args(["rev-parse", "HEAD"])
+///     .output()
+///     .ok()
+///     .and_then(|output| String::from_utf8(output.stdout).ok());
+/// if let Some(commit) = commit {
+///     println!("cargo:rustc-env=VERGEN_GIT_SHA={}", commit.trim());
+/// }
+/// println!(
+///     "cargo:rustc-env=VERGEN_CARGO_TARGET_TRIPLE={}",
+///     std::env::var("TARGET").unwrap()
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInformation {
+    /// The git commit hash
+    pub git_commit: Option<&'static str>,
+    /// If the working tree had uncommitted changes
+    pub git_dirty: Option<bool>,
+    /// The timestamp of the build
+    pub build_timestamp: Option<&'static str>,
+    /// The version of the Rust compiler
+    pub rustc_version: Option<&'static str>,
+    /// The enabled cargo features, comma separated
+    pub cargo_features: Option<&'static str>,
+    /// The target triple
+    pub target_triple: Option<&'static str>,
+}
+
+impl BuildInformation {
+    /// Check if any build information is present.
+    pub fn is_empty(&self) -> bool {
+        self.git_commit.is_none()
+            && self.git_dirty.is_none()
+            && self.build_timestamp.is_none()
+            && self.rustc_version.is_none()
+            && self.cargo_features.is_none()
+            && self.target_triple.is_none()
+    }
+
+    /// Iterate over the enabled cargo features.
+    pub fn features(&self) -> impl Iterator<Item = &'static str> {
+        self.cargo_features
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+}
+
+/// Parse a flag at compile time, used by [`component!`].
+#[doc(hidden)]
+pub const fn parse_flag(value: Option<&str>) -> Option<bool> {
+    match value {
+        Some(value) => match value.as_bytes() {
+            b"true" | b"1" => Some(true),
+            b"false" | b"0" => Some(false),
+            _ => None,
+        },
+        None => None,
+    }
+}
+
+impl Display for BuildInformation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        if let Some(commit) = self.git_commit {
+            match self.git_dirty {
+                Some(true) => parts.push(format!("{commit} (dirty)")),
+                _ => parts.push(commit.to_string()),
+            }
+        }
+        if let Some(timestamp) = self.build_timestamp {
+            parts.push(format!("built {timestamp}"));
+        }
+        if let Some(rustc) = self.rustc_version {
+            parts.push(format!("rustc {rustc}"));
+        }
+        if let Some(target) = self.target_triple {
+            parts.push(target.to_string());
+        }
+        if self.features().next().is_some() {
+            parts.push(format!(
+                "features: {}",
+                self.features().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        f.write_str(&parts.join(", "))
+    }
 }
 
 /// Create a new project information constant.
@@ -55,6 +203,8 @@ macro_rules! project {
 ///
 /// This will define a new constant, extracting the name of the component from the cargo file. It is
 /// intended to be directly used by [`runtime!`].
+///
+/// Build information will be captured if present, see [`BuildInformation`].
 #[macro_export]
 macro_rules! component {
     ($project:expr) => {
@@ -63,9 +213,81 @@ macro_rules! component {
             name: env!("CARGO_PKG_NAME"),
             version: env!("CARGO_PKG_VERSION"),
             description: env!("CARGO_PKG_DESCRIPTION"),
+            build: $crate::core::info::BuildInformation {
+                git_commit: option_env!("VERGEN_GIT_SHA"),
+                git_dirty: $crate::core::info::parse_flag(option_env!("VERGEN_GIT_DIRTY")),
+                build_timestamp: option_env!("VERGEN_BUILD_TIMESTAMP"),
+                rustc_version: option_env!("VERGEN_RUSTC_SEMVER"),
+                cargo_features: option_env!("VERGEN_CARGO_FEATURES"),
+                target_triple: option_env!("VERGEN_CARGO_TARGET_TRIPLE"),
+            },
         }
     };
     ($v:ident, $project:expr) => {
         pub const $v: $crate::core::info::ComponentInformation = $crate::component!($project);
     };
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_flag() {
+        const DIRTY: Option<bool> = parse_flag(Some("true"));
+
+        assert_eq!(DIRTY, Some(true));
+        assert_eq!(parse_flag(Some("0")), Some(false));
+        assert_eq!(parse_flag(Some("maybe")), None);
+        assert_eq!(parse_flag(None), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(BuildInformation::default().to_string(), "");
+
+        let build = BuildInformation {
+            git_commit: Some("abcdef"),
+            git_dirty: Some(true),
+            rustc_version: Some("1.61.0"),
+            cargo_features: Some("default, app"),
+            ..Default::default()
+        };
+        assert_eq!(
+            build.to_string(),
+            "abcdef (dirty), rustc 1.61.0, features: default, app"
+        );
+    }
+
+    #[test]
+    fn test_build_info_gauge() {
+        project!(PROJECT: "Test");
+        let mut component = component!(PROJECT);
+        component.build = BuildInformation {
+            build_timestamp: Some("2022-10-17T08:00:00Z"),
+            cargo_features: Some("default, app"),
+            ..Default::default()
+        };
+
+        let registry = Registry::new();
+        component.register_build_info(&registry).unwrap();
+
+        let families = registry.gather();
+        assert_eq!(families[0].get_name(), "build_info");
+        let metric = &families[0].get_metric()[0];
+        assert_eq!(metric.get_gauge().get_value(), 1.0);
+
+        let label = |name: &str| {
+            metric
+                .get_label()
+                .iter()
+                .find(|label| label.get_name() == name)
+                .map(|label| label.get_value().to_string())
+        };
+        assert_eq!(
+            label("build_timestamp").as_deref(),
+            Some("2022-10-17T08:00:00Z")
+        );
+        assert_eq!(label("features").as_deref(), Some("default,app"));
+    }
+}
@@ -8,6 +8,9 @@ pub mod default;
 pub mod info;
 /// Spawning tasks
 pub mod spawn;
+/// Named tasks and supervision
+#[cfg(feature = "app")]
+pub mod task;
 pub mod tls;
 
 pub use spawn::{Spawner, SpawnerExt};
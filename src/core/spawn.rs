@@ -6,6 +6,26 @@ use core::{future::Future, pin::Pin};
 /// gathered tasks to some concept like [`crate::app::Main`].
 pub trait Spawner {
     fn spawn_boxed(&mut self, future: Pin<Box<dyn Future<Output = anyhow::Result<()>>>>);
+
+    /// Spawn a named task, applying its policy.
+    ///
+    /// The default implementation spawns the task as an anonymous future.
+    #[cfg(feature = "app")]
+    fn spawn_task(&mut self, task: crate::core::task::Task) {
+        self.spawn_boxed(task.into_future())
+    }
+
+    /// Spawn a [`Send`] future, which gets executed on the (possibly multi-threaded) tokio
+    /// runtime.
+    ///
+    /// The completion of the future is handled in the same way as for any other future.
+    #[cfg(feature = "app")]
+    fn spawn_send_boxed(
+        &mut self,
+        future: futures_core::future::BoxFuture<'static, anyhow::Result<()>>,
+    ) {
+        self.spawn_boxed(crate::core::task::spawn_send(future))
+    }
 }
 
 pub trait SpawnerExt: Spawner {
@@ -24,6 +44,18 @@ pub trait SpawnerExt: Spawner {
     {
         self.spawn_boxed(Box::pin(f))
     }
+
+    /// Spawn a [`Send`] future, which gets executed on the (possibly multi-threaded) tokio
+    /// runtime.
+    ///
+    /// This is also available for [`crate::app::Startup`], which is a [`Spawner`] too.
+    #[cfg(feature = "app")]
+    fn spawn_send<F>(&mut self, f: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.spawn_send_boxed(Box::pin(f))
+    }
 }
 
 impl<S: ?Sized> SpawnerExt for S where S: Spawner {}
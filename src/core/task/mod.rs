@@ -0,0 +1,557 @@
+mod monitor;
+
+pub use monitor::*;
+
+use core::{convert::Infallible, fmt::Formatter, future::Future, time::Duration};
+use futures_core::future::{BoxFuture, LocalBoxFuture};
+use futures_util::{
+    future::{select, Either},
+    pin_mut, FutureExt,
+};
+use std::sync::Arc;
+use tokio::{sync::watch, task::JoinHandle};
+
+/// Triggers the shutdown of an application.
+///
+/// Clones of a trigger share the same state, so the shutdown can be triggered from several
+/// places.
+#[derive(Clone, Debug)]
+pub struct ShutdownTrigger {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ShutdownTrigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownTrigger {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Get a new token, notified when the shutdown is triggered.
+    pub fn token(&self) -> ShutdownToken {
+        ShutdownToken {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    /// Trigger the shutdown.
+    pub fn trigger(&self) {
+        self.sender.send_replace(true);
+    }
+}
+
+/// A token, allowing tasks to react to the shutdown of the application.
+///
+/// Tasks watching the token should be marked as [`Task::graceful`], so that the application
+/// waits for them to complete.
+///
+/// ```
+/// use drogue_bazaar::app::Startup;
+/// use drogue_bazaar::core::{task::Task, Spawner};
+///
+/// fn setup(startup: &mut dyn Startup) {
+///     let shutdown = startup.shutdown_token();
+///     startup.spawn_task(
+///         Task::new("worker", async move {
+///             loop {
+///                 tokio::select! {
+///                     _ = shutdown.triggered() => break,
+///                     _ = tokio::time::sleep(std::time::Duration::from_secs(1)) => {
+///                         // do some work
+///                     }
+///                 }
+///             }
+///             Ok(())
+///         })
+///         .graceful(),
+///     );
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShutdownToken {
+    receiver: watch::Receiver<bool>,
+}
+
+impl ShutdownToken {
+    /// Create a token which will never be triggered.
+    pub fn never() -> Self {
+        ShutdownTrigger::new().token()
+    }
+
+    /// Check if the shutdown was triggered.
+    pub fn is_triggered(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Wait until the shutdown was triggered.
+    pub async fn triggered(&self) {
+        let mut receiver = self.receiver.clone();
+        loop {
+            if *receiver.borrow_and_update() {
+                return;
+            }
+            if receiver.changed().await.is_err() {
+                // the trigger is gone, so this will never happen
+                futures_util::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Drive a future until it completes, starting the `stop` future once the shutdown is
+    /// triggered.
+    ///
+    /// This allows to gracefully stop e.g. a server, which must still be driven in order to
+    /// complete its shutdown.
+    pub async fn graceful<F, S>(self, future: F, stop: S) -> F::Output
+    where
+        F: Future,
+        S: Future<Output = ()>,
+    {
+        let stop = async move {
+            self.triggered().await;
+            stop.await;
+            futures_util::future::pending::<Infallible>().await
+        };
+
+        pin_mut!(future);
+        pin_mut!(stop);
+
+        match select(future, stop).await {
+            Either::Left((output, _)) => output,
+            Either::Right((never, _)) => match never {},
+        }
+    }
+}
+
+/// The policy applied when a task completes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskPolicy {
+    /// Stop the application when the task completes, successfully or not.
+    Critical,
+    /// Ignore the completion of the task, keeping the application running.
+    Ignore,
+    /// Restart the task when it completes, backing off on consecutive failures.
+    Restart(Backoff),
+}
+
+/// Exponential backoff for restarting tasks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backoff {
+    /// The delay before the first restart.
+    pub initial: Duration,
+    /// The maximum delay between restarts.
+    pub max: Duration,
+    /// The maximum number of consecutive restarts after failures, before the task is considered
+    /// failed. A successful completion resets the count.
+    pub max_restarts: Option<usize>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            max_restarts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// The delay after a number of consecutive failures.
+    pub fn delay(&self, failures: u32) -> Duration {
+        1u32.checked_shl(failures)
+            .and_then(|factor| self.initial.checked_mul(factor))
+            .unwrap_or(self.max)
+            .min(self.max)
+    }
+}
+
+enum Source {
+    Once(LocalBoxFuture<'static, anyhow::Result<()>>),
+    Factory(Box<dyn FnMut() -> LocalBoxFuture<'static, anyhow::Result<()>>>),
+}
+
+/// A named task, with a policy defining what happens when it completes.
+///
+/// ```
+/// use drogue_bazaar::core::{task::{Backoff, Task}, Spawner};
+///
+/// async fn consume() -> anyhow::Result<()> {
+///     // consume messages until the connection breaks
+///     Ok(())
+/// }
+///
+/// fn setup(spawner: &mut dyn Spawner) {
+///     spawner.spawn_task(Task::restarting("consumer", Backoff::default(), consume));
+/// }
+/// ```
+pub struct Task {
+    name: String,
+    policy: TaskPolicy,
+    graceful: bool,
+    source: Source,
+}
+
+impl core::fmt::Debug for Task {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Task")
+            .field("name", &self.name)
+            .field("policy", &self.policy)
+            .field("graceful", &self.graceful)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Task {
+    /// Create a new, critical, task.
+    pub fn new<N, F>(name: N, future: F) -> Self
+    where
+        N: Into<String>,
+        F: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        Self {
+            name: name.into(),
+            policy: TaskPolicy::Critical,
+            graceful: false,
+            source: Source::Once(future.boxed_local()),
+        }
+    }
+
+    /// Create a new task, whose completion will be ignored.
+    pub fn ignored<N, F>(name: N, future: F) -> Self
+    where
+        N: Into<String>,
+        F: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        Self {
+            name: name.into(),
+            policy: TaskPolicy::Ignore,
+            graceful: false,
+            source: Source::Once(future.boxed_local()),
+        }
+    }
+
+    /// Create a new task, which gets restarted using the factory when it completes.
+    ///
+    /// If the task exceeds the maximum number of restarts, it will be handled like a critical
+    /// task.
+    pub fn restarting<N, F, Fut>(name: N, backoff: Backoff, mut factory: F) -> Self
+    where
+        N: Into<String>,
+        F: FnMut() -> Fut + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + 'static,
+    {
+        Self {
+            name: name.into(),
+            policy: TaskPolicy::Restart(backoff),
+            graceful: false,
+            source: Source::Factory(Box::new(move || factory().boxed_local())),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn policy(&self) -> &TaskPolicy {
+        &self.policy
+    }
+
+    /// Mark the task as completing on its own, once the shutdown was triggered.
+    ///
+    /// During a graceful shutdown, the application waits for such tasks to complete. All other
+    /// tasks get dropped once the shutdown was triggered.
+    pub fn graceful(mut self) -> Self {
+        self.graceful = true;
+        self
+    }
+
+    pub fn is_graceful(&self) -> bool {
+        self.graceful
+    }
+
+    /// Convert into a future, applying the policy.
+    ///
+    /// The future only completes when the task should be considered completed by the
+    /// application. Tasks whose completion is ignored will never complete.
+    pub fn into_future(self) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+        self.into_supervised(ShutdownToken::never())
+    }
+
+    /// Convert into a future, applying the policy until the shutdown is triggered.
+    ///
+    /// Once the shutdown was triggered, tasks will no longer be restarted.
+    pub fn into_supervised(
+        self,
+        shutdown: ShutdownToken,
+    ) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+        self.into_monitored(shutdown, TaskMonitor::new())
+    }
+
+    /// Convert into a future, like [`Self::into_supervised`], reporting restarts and
+    /// completions of the task to the monitor.
+    ///
+    /// The start and final completion of the task must be tracked using [`TaskMonitor::track`].
+    pub fn into_monitored(
+        self,
+        shutdown: ShutdownToken,
+        monitor: TaskMonitor,
+    ) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+        let name = self.name;
+        match (self.policy, self.source) {
+            (TaskPolicy::Critical, Source::Once(future)) => future,
+            (TaskPolicy::Critical, Source::Factory(mut factory)) => factory(),
+            (TaskPolicy::Ignore, source) => async move {
+                let result = match source {
+                    Source::Once(future) => future.await,
+                    Source::Factory(mut factory) => factory().await,
+                };
+                monitor.completed(&name, &result);
+                log::info!("Task '{name}' completed, ignoring: {result:?}");
+                futures_util::future::pending().await
+            }
+            .boxed_local(),
+            (TaskPolicy::Restart(backoff), Source::Factory(factory)) => {
+                restart(name, backoff, factory, shutdown, monitor).boxed_local()
+            }
+            (TaskPolicy::Restart(_), Source::Once(future)) => future,
+        }
+    }
+}
+
+/// Run a [`Send`] future on the tokio runtime, which may be multi-threaded.
+///
+/// The future gets spawned when the returned future is first polled, and gets aborted when the
+/// returned future is dropped. A panic of the future is reported as an error.
+pub fn spawn_send(
+    future: BoxFuture<'static, anyhow::Result<()>>,
+) -> LocalBoxFuture<'static, anyhow::Result<()>> {
+    async move {
+        let mut handle = AbortOnDrop(tokio::spawn(future));
+        match (&mut handle.0).await {
+            Ok(result) => result,
+            Err(err) if err.is_panic() => anyhow::bail!("Task panicked"),
+            Err(err) => Err(err.into()),
+        }
+    }
+    .boxed_local()
+}
+
+struct AbortOnDrop(JoinHandle<anyhow::Result<()>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+async fn restart(
+    name: String,
+    backoff: Backoff,
+    mut factory: Box<dyn FnMut() -> LocalBoxFuture<'static, anyhow::Result<()>>>,
+    shutdown: ShutdownToken,
+    monitor: TaskMonitor,
+) -> anyhow::Result<()> {
+    let mut failures = 0;
+
+    loop {
+        let result = factory().await;
+
+        let delay = match &result {
+            Ok(()) => {
+                failures = 0;
+                backoff.delay(0)
+            }
+            Err(err) => {
+                let delay = backoff.delay(failures);
+                failures += 1;
+                log::warn!("Task '{name}' failed ({failures} consecutive failures): {err}");
+                delay
+            }
+        };
+
+        if shutdown.is_triggered() {
+            log::info!("Not restarting task '{name}', shutting down");
+            return result;
+        }
+
+        // every failure, except the first one, was preceded by a restart
+        if let (Some(max), Err(_)) = (backoff.max_restarts, &result) {
+            if failures as usize > max {
+                log::error!("Task '{name}' exceeded the maximum of {max} restarts");
+                return result.map_err(|err| {
+                    err.context(format!(
+                        "Task '{name}' exceeded the maximum of {max} restarts"
+                    ))
+                });
+            }
+        }
+        monitor.restarting(&name, &result);
+
+        log::info!(
+            "Restarting task '{name}' in {}",
+            humantime::format_duration(delay)
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = shutdown.triggered() => return result,
+        }
+        monitor.started(&name);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_backoff() {
+        let backoff = Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+            max_restarts: None,
+        };
+
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(backoff.delay(1), Duration::from_secs(2));
+        assert_eq!(backoff.delay(3), Duration::from_secs(8));
+        assert_eq!(backoff.delay(4), Duration::from_secs(10));
+        assert_eq!(backoff.delay(100), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_restart() {
+        let runs = Rc::new(Cell::new(0));
+
+        let task = Task::restarting(
+            "test",
+            Backoff {
+                initial: Duration::from_millis(1),
+                max: Duration::from_millis(10),
+                max_restarts: Some(3),
+            },
+            {
+                let runs = runs.clone();
+                move || {
+                    runs.set(runs.get() + 1);
+                    async { anyhow::bail!("failed") }
+                }
+            },
+        );
+
+        let monitor = TaskMonitor::new();
+        let result = monitor
+            .track(
+                "test",
+                task.into_monitored(ShutdownToken::never(), monitor.clone()),
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(runs.get(), 4);
+
+        let status = &monitor.tasks()[0];
+        assert_eq!(status.state, TaskState::Failed);
+        assert_eq!(status.restarts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_restart_resets() {
+        let runs = Rc::new(Cell::new(0));
+
+        // succeed on every third run, fail otherwise
+        let task = Task::restarting(
+            "test",
+            Backoff {
+                initial: Duration::from_millis(1),
+                max: Duration::from_millis(10),
+                max_restarts: Some(2),
+            },
+            {
+                let runs = runs.clone();
+                move || {
+                    runs.set(runs.get() + 1);
+                    let run = runs.get();
+                    async move {
+                        match run {
+                            run if run < 9 && run % 3 == 0 => Ok(()),
+                            _ => anyhow::bail!("failed"),
+                        }
+                    }
+                }
+            },
+        );
+
+        let result = task.into_future().await;
+
+        // runs 7 and 8 fail after the success of run 6, the failure of run 9 exceeds the limit
+        let err = result.unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "failed");
+        assert_eq!(runs.get(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_restart_shutdown() {
+        let trigger = ShutdownTrigger::new();
+        let runs = Rc::new(Cell::new(0));
+
+        let task = Task::restarting("test", Backoff::default(), {
+            let runs = runs.clone();
+            let token = trigger.token();
+            move || {
+                runs.set(runs.get() + 1);
+                let token = token.clone();
+                async move {
+                    token.triggered().await;
+                    Ok(())
+                }
+            }
+        });
+
+        let future = task.into_supervised(trigger.token());
+        trigger.trigger();
+
+        assert!(future.await.is_ok());
+        assert_eq!(runs.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_graceful() {
+        let trigger = ShutdownTrigger::new();
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+        let server = async move { rx.await.is_ok() };
+        let stop = async move {
+            tx.send(()).ok();
+        };
+
+        let future = trigger.token().graceful(server, stop);
+        trigger.trigger();
+
+        assert!(future.await);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_send() {
+        let result = spawn_send(async { anyhow::bail!("failed") }.boxed()).await;
+        assert_eq!(result.unwrap_err().to_string(), "failed");
+
+        let result = spawn_send(async { panic!("oops") }.boxed()).await;
+        assert_eq!(result.unwrap_err().to_string(), "Task panicked");
+    }
+
+    #[test]
+    fn test_ignored() {
+        let task = Task::ignored("test", async { Ok(()) });
+        assert!(task.into_future().now_or_never().is_none());
+    }
+}
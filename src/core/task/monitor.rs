@@ -0,0 +1,317 @@
+use chrono::{DateTime, Utc};
+use futures_core::future::LocalBoxFuture;
+use futures_util::FutureExt;
+use prometheus::{GaugeVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+/// The state of a task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskState {
+    /// The task was registered, but not yet started.
+    Pending,
+    /// The task is running.
+    Running,
+    /// The task completed and is waiting to be restarted.
+    Restarting,
+    /// The task completed successfully.
+    Completed,
+    /// The task failed.
+    Failed,
+}
+
+impl TaskState {
+    const ALL: [TaskState; 5] = [
+        Self::Pending,
+        Self::Running,
+        Self::Restarting,
+        Self::Completed,
+        Self::Failed,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Restarting => "restarting",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// The status of a task.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    /// The time the task was last (re-)started.
+    #[serde(serialize_with = "rfc3339")]
+    pub started: Option<DateTime<Utc>>,
+    /// The number of times the task was restarted.
+    pub restarts: u64,
+    /// The last error the task failed with.
+    pub last_error: Option<String>,
+}
+
+fn rfc3339<S: Serializer>(value: &Option<DateTime<Utc>>, s: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => s.serialize_some(&value.to_rfc3339()),
+        None => s.serialize_none(),
+    }
+}
+
+#[derive(Clone)]
+struct TaskMetrics {
+    state: IntGaugeVec,
+    running: IntGaugeVec,
+    start_time: GaugeVec,
+    restarts: IntCounterVec,
+    failures: IntCounterVec,
+}
+
+impl TaskMetrics {
+    fn new(registry: &Registry) -> prometheus::Result<Self> {
+        let state = IntGaugeVec::new(
+            Opts::new(
+                "task_state",
+                "The current state of the task, 1 for the active state",
+            ),
+            &["task", "state"],
+        )?;
+        let running = IntGaugeVec::new(
+            Opts::new("task_running", "If the task is currently running"),
+            &["task"],
+        )?;
+        let start_time = GaugeVec::new(
+            Opts::new(
+                "task_start_time_seconds",
+                "The time the task was last started, in seconds since the epoch",
+            ),
+            &["task"],
+        )?;
+        let restarts = IntCounterVec::new(
+            Opts::new("task_restarts_total", "The number of restarts of the task"),
+            &["task"],
+        )?;
+        let failures = IntCounterVec::new(
+            Opts::new("task_failures_total", "The number of failures of the task"),
+            &["task"],
+        )?;
+
+        registry.register(Box::new(state.clone()))?;
+        registry.register(Box::new(running.clone()))?;
+        registry.register(Box::new(start_time.clone()))?;
+        registry.register(Box::new(restarts.clone()))?;
+        registry.register(Box::new(failures.clone()))?;
+
+        Ok(Self {
+            state,
+            running,
+            start_time,
+            restarts,
+            failures,
+        })
+    }
+}
+
+/// Keeps track of the status of tasks.
+///
+/// A monitor can be shared between several instances, all tasks will be reported under their
+/// name. If a registry is provided, the status will also be exposed as metrics.
+#[derive(Clone, Default)]
+pub struct TaskMonitor {
+    tasks: Arc<Mutex<BTreeMap<String, TaskStatus>>>,
+    unnamed: Arc<AtomicUsize>,
+    metrics: Option<TaskMetrics>,
+}
+
+impl core::fmt::Debug for TaskMonitor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TaskMonitor")
+            .field("tasks", &self.tasks)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TaskMonitor {
+    /// Create a new monitor, without metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new monitor, registering its metrics with the registry.
+    pub fn with_registry(registry: &Registry) -> prometheus::Result<Self> {
+        Ok(Self {
+            tasks: Default::default(),
+            unnamed: Default::default(),
+            metrics: Some(TaskMetrics::new(registry)?),
+        })
+    }
+
+    /// Create a new name for an unnamed task, unique across all users of this monitor.
+    pub(crate) fn unnamed(&self) -> String {
+        format!("unnamed-{}", self.unnamed.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Get the status of all tasks, ordered by name.
+    pub fn tasks(&self) -> Vec<TaskStatus> {
+        self.tasks.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Track the execution of a task.
+    ///
+    /// The task gets registered as pending, and is considered running once the future gets
+    /// polled for the first time.
+    pub fn track<'f, F>(&self, name: &str, future: F) -> LocalBoxFuture<'f, anyhow::Result<()>>
+    where
+        F: Future<Output = anyhow::Result<()>> + 'f,
+    {
+        self.update(name, |_| {});
+
+        let monitor = self.clone();
+        let name = name.to_string();
+        async move {
+            monitor.started(&name);
+            let result = future.await;
+            monitor.completed(&name, &result);
+            result
+        }
+        .boxed_local()
+    }
+
+    /// Record that a task was (re-)started.
+    pub(crate) fn started(&self, name: &str) {
+        let now = Utc::now();
+        self.update(name, |status| {
+            status.state = TaskState::Running;
+            status.started = Some(now);
+        });
+        if let Some(metrics) = &self.metrics {
+            metrics.running.with_label_values(&[name]).set(1);
+            metrics
+                .start_time
+                .with_label_values(&[name])
+                .set(now.timestamp_millis() as f64 / 1000.0);
+        }
+    }
+
+    /// Record that a task completed.
+    pub(crate) fn completed(&self, name: &str, result: &anyhow::Result<()>) {
+        self.stopped(name, result, false);
+    }
+
+    /// Record that a task completed, and will be restarted.
+    pub(crate) fn restarting(&self, name: &str, result: &anyhow::Result<()>) {
+        self.stopped(name, result, true);
+        if let Some(metrics) = &self.metrics {
+            metrics.restarts.with_label_values(&[name]).inc();
+        }
+    }
+
+    fn stopped(&self, name: &str, result: &anyhow::Result<()>, restarting: bool) {
+        self.update(name, |status| {
+            status.state = match (restarting, result) {
+                (true, _) => TaskState::Restarting,
+                (false, Ok(())) => TaskState::Completed,
+                (false, Err(_)) => TaskState::Failed,
+            };
+            if restarting {
+                status.restarts += 1;
+            }
+            if let Err(err) = result {
+                status.last_error = Some(format!("{err:#}"));
+            }
+        });
+        if let Some(metrics) = &self.metrics {
+            metrics.running.with_label_values(&[name]).set(0);
+            if result.is_err() {
+                metrics.failures.with_label_values(&[name]).inc();
+            }
+        }
+    }
+
+    fn update<F>(&self, name: &str, f: F)
+    where
+        F: FnOnce(&mut TaskStatus),
+    {
+        let mut tasks = self.tasks.lock().unwrap();
+        let status = tasks.entry(name.to_string()).or_insert_with(|| TaskStatus {
+            name: name.to_string(),
+            state: TaskState::Pending,
+            started: None,
+            restarts: 0,
+            last_error: None,
+        });
+        f(status);
+
+        if let Some(metrics) = &self.metrics {
+            for state in TaskState::ALL {
+                metrics
+                    .state
+                    .with_label_values(&[name, state.as_str()])
+                    .set((state == status.state) as i64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track() {
+        let registry = Registry::new();
+        let monitor = TaskMonitor::with_registry(&registry).unwrap();
+
+        let task = monitor.track("test", async { anyhow::bail!("oops") });
+        assert_eq!(monitor.tasks()[0].state, TaskState::Pending);
+
+        assert!(task.await.is_err());
+
+        let status = &monitor.tasks()[0];
+        assert_eq!(status.state, TaskState::Failed);
+        assert!(status.started.is_some());
+        assert_eq!(status.last_error.as_deref(), Some("oops"));
+
+        let failures = registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == "task_failures_total")
+            .unwrap();
+        assert_eq!(failures.get_metric()[0].get_counter().get_value(), 1.0);
+
+        let state = registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == "task_state")
+            .unwrap();
+        let failed = state
+            .get_metric()
+            .iter()
+            .find(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .any(|label| label.get_name() == "state" && label.get_value() == "failed")
+            })
+            .unwrap();
+        assert_eq!(failed.get_gauge().get_value(), 1.0);
+    }
+
+    #[test]
+    fn test_unnamed() {
+        let monitor = TaskMonitor::new();
+        let other = monitor.clone();
+        assert_eq!(monitor.unnamed(), "unnamed-0");
+        assert_eq!(other.unnamed(), "unnamed-1");
+    }
+}
@@ -3,6 +3,7 @@
 mod auth;
 pub use auth::*;
 
+use crate::core::config::{ConfigReference, Reference};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -22,6 +23,27 @@ pub struct ClientConfig {
     pub ca_certificate: Option<String>,
 }
 
+impl ConfigReference for ClientConfig {
+    fn describe(r: &mut Reference) {
+        r.with_default(
+            "tls_insecure",
+            "boolean",
+            false,
+            "Disable TLS validation, do not use this in production",
+        );
+        r.optional(
+            "ca_certificates",
+            "list",
+            "Additional CA certificates (PEM files)",
+        );
+        r.optional(
+            "ca_certificate",
+            "path",
+            "An additional CA certificate (PEM file)",
+        );
+    }
+}
+
 impl ClientConfig {
     pub fn certificates(&self) -> impl Iterator<Item = &str> {
         let service_ca = {
@@ -71,6 +93,7 @@ impl TryFrom<&ClientConfig> for native_tls::TlsConnector {
 mod test {
 
     use super::*;
+    use crate::core::config::testing::assert_reference;
     use crate::core::config::ConfigFromEnv;
     use config::Environment;
     use std::collections::HashMap;
@@ -99,4 +122,9 @@ mod test {
             vec!["/path/to/file"]
         )
     }
+
+    #[test]
+    fn test_reference() {
+        assert_reference::<ClientConfig>();
+    }
 }
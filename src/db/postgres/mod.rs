@@ -1,16 +1,62 @@
 //! Basic PostgreSQL support
 
-use crate::core::tls::ClientConfig;
+use crate::{
+    core::{config::REDACTED, tls::ClientConfig},
+    health::{HealthCheckError, HealthChecked},
+};
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use serde::{Serialize, Serializer};
 
 /// A Postgres pooled connection configuration
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+///
+/// The [`Debug`] and [`Serialize`] implementations redact the password of the database
+/// configuration.
+#[derive(Clone, serde::Deserialize)]
 pub struct Config {
     pub db: deadpool_postgres::Config,
     #[serde(default)]
     pub tls: ClientConfig,
 }
 
+impl Debug for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Config")
+            .field("db", &self.redacted_db())
+            .field("tls", &self.tls)
+            .finish()
+    }
+}
+
+impl Serialize for Config {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct Redacted<'a> {
+            db: deadpool_postgres::Config,
+            tls: &'a ClientConfig,
+        }
+
+        Redacted {
+            db: self.redacted_db(),
+            tls: &self.tls,
+        }
+        .serialize(serializer)
+    }
+}
+
 impl Config {
+    /// The database configuration, with the password redacted.
+    fn redacted_db(&self) -> deadpool_postgres::Config {
+        let mut db = self.db.clone();
+        if db.password.is_some() {
+            db.password = Some(REDACTED.to_string());
+        }
+        db
+    }
+
     /// Create a pool from a configuration.
     pub fn create_pool(&self) -> anyhow::Result<deadpool_postgres::Pool> {
         Ok(self.db.create_pool(
@@ -19,3 +65,69 @@ impl Config {
         )?)
     }
 }
+
+/// A health check, verifying that a connection can be acquired from the pool and is working.
+///
+/// The check only affects the readiness of the application.
+#[derive(Clone)]
+pub struct PoolHealthCheck {
+    pool: deadpool_postgres::Pool,
+}
+
+impl PoolHealthCheck {
+    pub fn new(pool: deadpool_postgres::Pool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a new pool from the configuration, and a check for it.
+    ///
+    /// The pool can be accessed using [`Self::pool`], so that the application can share it.
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self::new(config.create_pool()?))
+    }
+
+    pub fn pool(&self) -> &deadpool_postgres::Pool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl HealthChecked for PoolHealthCheck {
+    fn name(&self) -> String {
+        "postgres".into()
+    }
+
+    async fn is_ready(&self) -> Result<(), HealthCheckError> {
+        let client = self.pool.get().await.map_err(HealthCheckError::from)?;
+        client
+            .simple_query("SELECT 1")
+            .await
+            .map_err(HealthCheckError::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_redacted() {
+        let mut db = deadpool_postgres::Config::new();
+        db.user = Some("user".into());
+        db.password = Some("s3cr3t".into());
+        let config = Config {
+            db,
+            tls: Default::default(),
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("user"));
+        assert!(!json.contains("s3cr3t"));
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("s3cr3t"));
+
+        assert_eq!(config.db.password.as_deref(), Some("s3cr3t"));
+    }
+}
@@ -23,8 +23,36 @@ impl HealthCheckError {
     }
 }
 
+/// The impact of a failing health check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Criticality {
+    /// A failure fails the readiness and liveness probes.
+    Critical,
+    /// A failure only reports the application as degraded, without failing the probes.
+    NonCritical,
+    /// The check is only considered for the liveness probe, where a failure fails the probe.
+    LivenessOnly,
+}
+
+impl Default for Criticality {
+    fn default() -> Self {
+        Self::Critical
+    }
+}
+
 #[async_trait]
 pub trait HealthChecked: Send + Sync {
+    /// The name of the check, defaults to the name of the type.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// The criticality of the check, defaults to [`Criticality::Critical`].
+    fn criticality(&self) -> Criticality {
+        Criticality::Critical
+    }
+
     async fn is_ready(&self) -> Result<(), HealthCheckError> {
         Ok(())
     }
@@ -1,6 +1,10 @@
 //! Support for using `reqwest`.
 
-use crate::core::tls::ClientConfig;
+use crate::{
+    core::tls::ClientConfig,
+    health::{Criticality, HealthCheckError, HealthChecked},
+};
+use async_trait::async_trait;
 use reqwest::Certificate;
 use std::{
     fs::File,
@@ -8,6 +12,7 @@ use std::{
     path::{Path, PathBuf},
     str::FromStr,
 };
+use url::Url;
 
 /// Convert the name to an HTTP method.
 ///
@@ -142,3 +147,125 @@ impl ClientFactory {
         self.new_client()
     }
 }
+
+/// A health check, verifying that an HTTP endpoint responds with a successful status code.
+pub struct HttpEndpointCheck {
+    name: String,
+    client: reqwest::Client,
+    url: Url,
+    criticality: Criticality,
+}
+
+impl HttpEndpointCheck {
+    /// Create a new check, using a client created by the factory.
+    pub fn new<N: Into<String>>(
+        name: N,
+        factory: &ClientFactory,
+        url: Url,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: name.into(),
+            client: factory.new_client()?,
+            url,
+            criticality: Criticality::Critical,
+        })
+    }
+
+    /// Set the criticality of the check, defaults to [`Criticality::Critical`].
+    pub fn with_criticality(mut self, criticality: Criticality) -> Self {
+        self.criticality = criticality;
+        self
+    }
+}
+
+#[async_trait]
+impl HealthChecked for HttpEndpointCheck {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn criticality(&self) -> Criticality {
+        self.criticality
+    }
+
+    async fn is_ready(&self) -> Result<(), HealthCheckError> {
+        self.check().await
+    }
+
+    async fn is_alive(&self) -> Result<(), HealthCheckError> {
+        match self.criticality {
+            Criticality::LivenessOnly => self.check().await,
+            _ => Ok(()),
+        }
+    }
+}
+
+impl HttpEndpointCheck {
+    async fn check(&self) -> Result<(), HealthCheckError> {
+        self.client
+            .get(self.url.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(HealthCheckError::from)
+    }
+}
+
+#[cfg(all(test, feature = "app"))]
+mod test {
+    use super::*;
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Response, Server, StatusCode,
+    };
+    use std::convert::Infallible;
+
+    /// Run a local server, responding to all requests with the status code.
+    fn serve(status: StatusCode) -> Url {
+        let make = make_service_fn(move |_| async move {
+            Ok::<_, Infallible>(service_fn(move |_| async move {
+                Ok::<_, Infallible>(
+                    Response::builder()
+                        .status(status)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+            }))
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make);
+        let url = Url::parse(&format!("http://{}/", server.local_addr())).unwrap();
+        tokio::spawn(server);
+
+        url
+    }
+
+    fn check(status: StatusCode) -> HttpEndpointCheck {
+        HttpEndpointCheck::new("endpoint", &ClientFactory::new(), serve(status)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ok() {
+        let check = check(StatusCode::OK);
+        assert!(check.is_ready().await.is_ok());
+        assert!(check.is_alive().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_not_ok() {
+        let check = check(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(check.is_ready().await.is_err());
+        // only checked for readiness, unless it is liveness only
+        assert!(check.is_alive().await.is_ok());
+
+        let check = check.with_criticality(Criticality::LivenessOnly);
+        assert!(check.is_alive().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_not_found() {
+        let check = check(StatusCode::NOT_FOUND);
+        assert!(check.is_ready().await.is_err());
+    }
+}